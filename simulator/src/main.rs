@@ -0,0 +1,188 @@
+//! Draws the screens of the firmware off the device, from the recorded HKO payloads, so
+//! layouts can be checked without flashing. From this directory:
+//!
+//! `cargo run --target x86_64-unknown-linux-gnu -- [layout] [out-dir]`
+//!
+//! writes `<panel>/<layout>-black.pbm` and `<panel>/<layout>-chromatic.pbm`, the two images
+//! the panel is sent, and a `<panel>/<layout>.ppm` preview of them together, for each of the
+//! panels in `PANELS`. Every layout is drawn when none is given, into `target/simulator`
+//! when no directory is.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use epd_waveshare::{epd2in9b_v4, epd4in2, epd7in5_v2};
+use weather_core::frame::{Plane, TriColorFrame};
+use weather_core::hko;
+use weather_core::hko_response::{
+    CurrentWeatherResponse, NineDayForecastResponse, WarningSummaryResponse,
+};
+use weather_core::json::parse_json;
+use weather_core::model::{LocalForecastText, NineDayForecast, WarningSignal, WeatherReport};
+use weather_core::screen::{self, Freshness, Screen};
+use weather_core::text_layout::paginate;
+
+const FND_FIXTURE: &str = include_str!("../../weather-core/tests/fixtures/hko/fnd.json");
+const WARNSUM_FIXTURE: &str = include_str!("../../weather-core/tests/fixtures/hko/warnsum.json");
+const RHRREAD_FIXTURE: &str = include_str!("../../weather-core/tests/fixtures/hko/rhrread.json");
+
+/// Station and district the current weather is read for, as the firmware does by default
+const STATION: &str = "Sham Shui Po";
+
+const LAYOUTS: [&str; 4] = ["weather", "forecast-text", "current-weather", "text-page"];
+
+/// Sizes the firmware can be built for, as drawn. The 2.9" panel is drawn rotated, its long
+/// side across.
+const PANELS: [(&str, Size); 3] = [
+    ("2in9", Size::new(epd2in9b_v4::HEIGHT, epd2in9b_v4::WIDTH)),
+    ("4in2", Size::new(epd4in2::WIDTH, epd4in2::HEIGHT)),
+    ("7in5", Size::new(epd7in5_v2::WIDTH, epd7in5_v2::HEIGHT)),
+];
+
+/// Minutes between the forecast's update time and the clock the sidebar is drawn with
+const MINUTES_SINCE_UPDATE: i64 = 20;
+
+/// Ink of the third color on the 2.9" B panel, the others leave it black
+const CHROMATIC: Rgb888 = Rgb888::RED;
+
+struct Fixtures {
+    forecast: NineDayForecast,
+    current_weather: WeatherReport,
+    warnings: Vec<WarningSignal>,
+}
+
+impl Fixtures {
+    fn load() -> Self {
+        let response: NineDayForecastResponse = parse_json(FND_FIXTURE).unwrap();
+        let warnings: WarningSummaryResponse = parse_json(WARNSUM_FIXTURE).unwrap();
+        let current_weather: CurrentWeatherResponse = parse_json(RHRREAD_FIXTURE).unwrap();
+
+        Self {
+            forecast: response.try_into().unwrap(),
+            current_weather: hko::current_weather(current_weather, STATION, STATION).unwrap(),
+            warnings: hko::warning_signals(&warnings),
+        }
+    }
+
+    /// Today's description for the third column, in the shape of HKO's `flw`
+    fn forecast_text(&self) -> LocalForecastText {
+        LocalForecastText {
+            general_situation: self.forecast.general_situation.clone(),
+            tc_info: String::new(),
+            fire_danger_warning: String::new(),
+            forecast_period: "Weather forecast for today".to_owned(),
+            forecast_desc: "Mainly cloudy with a few showers and isolated squally \
+                            thunderstorms. Hot with sunny intervals during the day."
+                .to_owned(),
+            outlook: String::new(),
+            update_time: self.forecast.update_time,
+        }
+    }
+}
+
+/// The forecast and the sidebar, as the firmware leaves them after a refresh
+fn draw_weather(
+    fixtures: &Fixtures,
+    size: Size,
+    with_forecast_text: bool,
+    with_current_weather: bool,
+) -> TriColorFrame {
+    let mut frame = TriColorFrame::new(size);
+    let mut screen = Screen::new(size);
+    screen.set_current_weather(with_current_weather.then_some(&fixtures.current_weather));
+    let update_time = fixtures.forecast.update_time;
+    let now = update_time.unix_time() + MINUTES_SINCE_UPDATE * 60;
+    let forecast_text = with_forecast_text.then(|| fixtures.forecast_text());
+
+    screen.draw_weather(
+        &mut frame,
+        &fixtures.forecast.forecasts,
+        &fixtures.warnings,
+        None,
+        forecast_text.as_ref(),
+        Freshness::Fetched(Some(update_time)),
+    );
+    screen.draw_sidebar(&mut frame, now);
+    screen::draw_reading(
+        &mut frame.translated(screen.temperature_reading().top_left),
+        "27.5C",
+        screen.sidebar_scale(),
+    );
+    screen::draw_reading(
+        &mut frame.translated(screen.humidity_reading().top_left),
+        "68.0%",
+        screen.sidebar_scale(),
+    );
+
+    frame
+}
+
+/// First page of a weather tip, with the general situation standing in for the tip
+fn draw_text_page(fixtures: &Fixtures, size: Size) -> TriColorFrame {
+    let mut frame = TriColorFrame::new(size);
+    let screen = Screen::new(size);
+    let pages = paginate(
+        std::slice::from_ref(&fixtures.forecast.general_situation),
+        screen.text_page_columns(),
+        screen.text_page_lines(),
+    );
+
+    screen.draw_text_page(&mut frame, "WEATHER TIP", &pages[0], 0, pages.len());
+
+    frame
+}
+
+fn write_frame(frame: &TriColorFrame, out_dir: &Path, layout: &str) -> io::Result<()> {
+    fs::write(
+        out_dir.join(format!("{}-black.pbm", layout)),
+        frame.to_pbm(Plane::Black),
+    )?;
+    fs::write(
+        out_dir.join(format!("{}-chromatic.pbm", layout)),
+        frame.to_pbm(Plane::Chromatic),
+    )?;
+    fs::write(
+        out_dir.join(format!("{}.ppm", layout)),
+        frame.to_ppm(CHROMATIC),
+    )?;
+
+    println!("{}", out_dir.join(layout).display());
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let layouts: Vec<&str> = match args.next() {
+        Some(layout) => match LAYOUTS.iter().find(|&&name| name == layout) {
+            Some(&name) => vec![name],
+            None => {
+                eprintln!("Unknown layout {}, expected one of {:?}", layout, LAYOUTS);
+                process::exit(2);
+            }
+        },
+        None => LAYOUTS.to_vec(),
+    };
+    let out_dir = args.next().unwrap_or_else(|| "target/simulator".to_owned());
+    let fixtures = Fixtures::load();
+
+    for (panel, size) in PANELS {
+        let out_dir = Path::new(&out_dir).join(panel);
+        fs::create_dir_all(&out_dir)?;
+
+        for &layout in &layouts {
+            let frame = match layout {
+                "weather" => draw_weather(&fixtures, size, false, false),
+                "forecast-text" => draw_weather(&fixtures, size, true, false),
+                "current-weather" => draw_weather(&fixtures, size, false, true),
+                _ => draw_text_page(&fixtures, size),
+            };
+
+            write_frame(&frame, &out_dir, layout)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs};
+use weather_core::dashboard::WeatherSource;
+use weather_core::http::{HttpClient, DEFAULT_MAX_BODY_BYTES};
+use weather_core::met_norway::utc_offset;
+use weather_core::met_norway_api::{MetNorwayApi, DEFAULT_USER_AGENT};
+use weather_core::model::{Language, Location};
+use weather_core::open_meteo_api::OpenMeteoApi;
+use weather_core::scheduler::RefreshSchedule;
+use weather_core::weather_api::{WeatherApi, WeatherApiConfig};
+
+/// NVS namespace holding the device settings
+const CONFIG_NAMESPACE: &str = "weather";
+
+/// Weather service the forecast is fetched from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProviderKind {
+    /// Hong Kong Observatory, with warnings, tips and the local forecast text
+    Hko,
+    /// Open-Meteo, forecasts for any latitude and longitude
+    OpenMeteo,
+    /// MET Norway locationforecast, forecasts for any latitude and longitude
+    MetNorway,
+}
+
+impl ProviderKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hko" => Some(Self::Hko),
+            "open_meteo" => Some(Self::OpenMeteo),
+            "met_norway" => Some(Self::MetNorway),
+            _ => None,
+        }
+    }
+}
+
+pub struct AppConfig {
+    pub provider: ProviderKind,
+    pub weather_api: WeatherApiConfig,
+    /// Where Open-Meteo and MET Norway forecast for
+    pub location: Location,
+    /// Sent to MET Norway, which blocks clients without a way to contact their developer
+    pub user_agent: String,
+    /// Show today's forecast description beside four forecast days instead of six days, off
+    /// unless the `forecast_text` key turns it on
+    pub show_forecast_text: bool,
+    /// Fetch the readings of the configured station and show them in the first cell, ahead
+    /// of the forecast days, off unless the `current_weather` key turns it on
+    pub show_current_weather: bool,
+    pub refresh_schedule: RefreshSchedule,
+    /// Weather fetched longer ago than this is marked as old
+    pub stale_after: Duration,
+    /// Responses with a longer body are rejected rather than read
+    pub max_body_bytes: usize,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::Hko,
+            weather_api: WeatherApiConfig::default(),
+            location: Location::default(),
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            show_forecast_text: false,
+            show_current_weather: false,
+            refresh_schedule: RefreshSchedule::default(),
+            // Three missed hourly refreshes
+            stale_after: Duration::from_secs(3 * 60 * 60),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Minutes east of UTC that times are shown in, Hong Kong time for HKO and the zone
+    /// estimated from the longitude elsewhere
+    pub fn utc_offset_minutes(&self) -> i16 {
+        match self.provider {
+            ProviderKind::Hko => 8 * 60,
+            ProviderKind::OpenMeteo | ProviderKind::MetNorway => {
+                (utc_offset(&self.location) / 60) as i16
+            }
+        }
+    }
+
+    /// The configured provider, fetching through `client`
+    pub fn weather_source<C: HttpClient>(&self, client: C) -> WeatherSource<C> {
+        match self.provider {
+            ProviderKind::Hko => {
+                WeatherSource::Hko(WeatherApi::new(client, self.weather_api.clone()))
+            }
+            ProviderKind::OpenMeteo => {
+                WeatherSource::OpenMeteo(OpenMeteoApi::new(client, self.location.clone()))
+            }
+            ProviderKind::MetNorway => WeatherSource::MetNorway(MetNorwayApi::new(
+                client,
+                self.location.clone(),
+                self.user_agent.clone(),
+            )),
+        }
+    }
+
+    /// Read the settings from NVS, any key that is not set keeps its default value
+    pub fn load(nvs: EspDefaultNvsPartition) -> anyhow::Result<Self> {
+        let storage = EspNvs::new(nvs, CONFIG_NAMESPACE, true)?;
+        let mut config = Self::default();
+        let mut buffer = [0_u8; 128];
+
+        if let Some(code) = storage.get_str("lang", &mut buffer)? {
+            match Language::from_api_code(code) {
+                Some(language) => config.weather_api = WeatherApiConfig::new(language),
+                None => log::warn!("Unknown language {}, expected en, tc or sc", code),
+            }
+        }
+
+        if let Some(station) = storage.get_str("station", &mut buffer)? {
+            config.weather_api.temperature_station = station.to_owned();
+        }
+
+        if let Some(district) = storage.get_str("rain_district", &mut buffer)? {
+            config.weather_api.rainfall_district = district.to_owned();
+        }
+
+        if let Some(show_forecast_text) = storage.get_u8("forecast_text")? {
+            config.show_forecast_text = show_forecast_text != 0;
+        }
+
+        if let Some(show_current_weather) = storage.get_u8("current_weather")? {
+            config.show_current_weather = show_current_weather != 0;
+        }
+
+        if let Some(name) = storage.get_str("provider", &mut buffer)? {
+            match ProviderKind::from_name(name) {
+                Some(provider) => config.provider = provider,
+                None => log::warn!(
+                    "Unknown provider {}, expected hko, open_meteo or met_norway",
+                    name
+                ),
+            }
+        }
+
+        // NVS has no floats, coordinates are stored as strings such as "22.3303"
+        if let Some(latitude) = storage.get_str("latitude", &mut buffer)? {
+            match latitude.parse() {
+                Ok(latitude) => config.location.latitude = latitude,
+                Err(_) => log::warn!("Invalid latitude {}", latitude),
+            }
+        }
+
+        if let Some(longitude) = storage.get_str("longitude", &mut buffer)? {
+            match longitude.parse() {
+                Ok(longitude) => config.location.longitude = longitude,
+                Err(_) => log::warn!("Invalid longitude {}", longitude),
+            }
+        }
+
+        if let Some(place) = storage.get_str("place", &mut buffer)? {
+            config.location.place = place.to_owned();
+        }
+
+        if let Some(user_agent) = storage.get_str("user_agent", &mut buffer)? {
+            config.user_agent = user_agent.to_owned();
+        }
+
+        // Minutes between refreshes and minutes past each aligned slot, e.g. 60 and 10 for 10:10,
+        // 11:10, ... Hong Kong time
+        let interval = storage.get_u16("refresh_min")?;
+        let offset = storage.get_u16("refresh_offset")?;
+        if interval.is_some() || offset.is_some() {
+            config.refresh_schedule =
+                RefreshSchedule::new(interval.unwrap_or(60), offset.unwrap_or(10));
+        }
+
+        if let Some(minutes) = storage.get_u16("stale_min")? {
+            config.stale_after = Duration::from_secs(u64::from(minutes) * 60);
+        }
+
+        if let Some(kilobytes) = storage.get_u16("max_body_kb")? {
+            config.max_body_bytes = usize::from(kilobytes) * 1024;
+        }
+
+        log::info!(
+            "provider: {:?}, location: {}, {} ({})",
+            config.provider,
+            config.location.latitude,
+            config.location.longitude,
+            config.location.place
+        );
+        log::info!(
+            "language: {:?}, station: {}, rainfall district: {}",
+            config.weather_api.language,
+            config.weather_api.temperature_station,
+            config.weather_api.rainfall_district
+        );
+        log::info!(
+            "refresh: {:?}, stale after: {:?}",
+            config.refresh_schedule,
+            config.stale_after
+        );
+
+        Ok(config)
+    }
+}
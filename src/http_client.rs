@@ -1,18 +1,34 @@
+use std::io;
+
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     hal::modem::Modem,
     http::client::{Configuration, EspHttpConnection},
+    io::EspIOError,
     nvs::EspDefaultNvsPartition,
+    sys,
     wifi::{AuthMethod, BlockingWifi, ClientConfiguration, EspWifi},
 };
 
-use embedded_svc::{http::client::Client, wifi::Configuration as WiFiConfiguration};
+use embedded_svc::{
+    http::{
+        client::{Client, Response},
+        Method,
+    },
+    wifi::Configuration as WiFiConfiguration,
+};
+use serde::de::DeserializeOwned;
+use weather_core::http::{HttpClient, HttpResponse, RESPONSE_HEADERS};
+use weather_core::json::{parse_json_reader, LimitedReader, READ_BUFFER_BYTES};
+use weather_core::model::ApiError;
 
 use crate::wifi_config::{SSID, WIFI_PASSWORD};
 
-pub fn setup_wifi(modem: &mut Modem) -> anyhow::Result<BlockingWifi<EspWifi>> {
+pub fn setup_wifi(
+    modem: Modem,
+    nvs: EspDefaultNvsPartition,
+) -> anyhow::Result<BlockingWifi<EspWifi<'static>>> {
     let sysloop = EspSystemEventLoop::take()?;
-    let nvs = EspDefaultNvsPartition::take()?;
 
     let mut wifi = BlockingWifi::wrap(
         EspWifi::new(modem, sysloop.clone(), Some(nvs)).unwrap(),
@@ -31,22 +47,142 @@ pub fn setup_wifi(modem: &mut Modem) -> anyhow::Result<BlockingWifi<EspWifi>> {
     // Start Wifi
     wifi.start()?;
 
-    // Connect Wifi
+    Ok(wifi)
+}
+
+/// Join the access point, again after a failed attempt
+pub fn connect_wifi(wifi: &mut BlockingWifi<EspWifi>) -> anyhow::Result<()> {
     wifi.connect()?;
 
     // Wait until the network interface is up
     wifi.wait_netif_up()?;
 
-    Ok(wifi)
+    Ok(())
 }
 
-pub fn get_http_client() -> Client<EspHttpConnection> {
+pub fn get_http_client() -> anyhow::Result<Client<EspHttpConnection>> {
     let conn = EspHttpConnection::new(&Configuration {
         use_global_ca_store: true,
         crt_bundle_attach: Some(esp_idf_sys::esp_crt_bundle_attach),
         ..Default::default()
-    })
-    .unwrap();
+    })?;
+
+    Ok(Client::wrap(conn))
+}
+
+fn is_timeout(err: &EspIOError) -> bool {
+    let code = err.0.code();
+
+    code == sys::ESP_ERR_TIMEOUT as sys::esp_err_t
+        || code == sys::ESP_ERR_HTTP_EAGAIN as sys::esp_err_t
+}
+
+fn esp_error(err: EspIOError) -> ApiError {
+    if is_timeout(&err) {
+        ApiError::Timeout(Box::new(err))
+    } else {
+        ApiError::Transport(Box::new(err))
+    }
+}
+
+/// `std::io::Read` over the body, with ESP timeouts as `io::ErrorKind::TimedOut` so
+/// `ApiError::from(io::Error)` tells them apart
+struct ResponseReader<'a>(Response<&'a mut EspHttpConnection>);
+
+impl io::Read for ResponseReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(|err| {
+            let kind = if is_timeout(&err) {
+                io::ErrorKind::TimedOut
+            } else {
+                io::ErrorKind::Other
+            };
+            io::Error::new(kind, err)
+        })
+    }
+}
+
+/// The ESP-IDF HTTP client with a cap on how much of a body it reads
+pub struct EspHttpClient {
+    client: Client<EspHttpConnection>,
+    max_body_bytes: usize,
+}
+
+impl EspHttpClient {
+    pub fn new(client: Client<EspHttpConnection>, max_body_bytes: usize) -> Self {
+        Self {
+            client,
+            max_body_bytes,
+        }
+    }
+
+    /// The body is left to be read
+    fn submit<'a>(
+        &'a mut self,
+        url: &'a str,
+        headers: &'a [(&'a str, &'a str)],
+    ) -> Result<HttpResponse<LimitedReader<ResponseReader<'a>>>, ApiError> {
+        let req = self
+            .client
+            .request(Method::Get, url, headers)
+            .map_err(esp_error)?;
+        let res = req.submit().map_err(esp_error)?;
+
+        let status = res.status();
+        let response_headers = RESPONSE_HEADERS
+            .iter()
+            .filter_map(|&name| Some((name.to_owned(), res.header(name)?.to_owned())))
+            .collect();
+
+        Ok(HttpResponse {
+            status,
+            headers: response_headers,
+            body: LimitedReader::new(ResponseReader(res), self.max_body_bytes),
+        })
+    }
+}
+
+impl HttpClient for EspHttpClient {
+    fn get_request(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, ApiError> {
+        let mut response = self.submit(url, headers)?;
+
+        let mut result: Vec<u8> = Vec::new();
+        io::Read::read_to_end(&mut response.body, &mut result)?;
+        let string =
+            String::from_utf8(result).map_err(|err| ApiError::InvalidUtf8(err.utf8_error()))?;
+
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers,
+            body: string,
+        })
+    }
+
+    fn get_json<T: DeserializeOwned>(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse<Option<T>>, ApiError> {
+        let mut response = self.submit(url, headers)?;
+
+        let body = if response.is_success() {
+            // serde_json reads a byte at a time
+            let reader = io::BufReader::with_capacity(READ_BUFFER_BYTES, response.body);
+            Some(parse_json_reader(reader)?)
+        } else {
+            // Read to the end so the connection can be reused
+            io::copy(&mut response.body, &mut io::sink())?;
+            None
+        };
 
-    Client::wrap(conn)
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers,
+            body,
+        })
+    }
 }
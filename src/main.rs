@@ -1,23 +1,66 @@
-mod edp_display;
+mod config;
 mod http_client;
-mod model;
-mod weather_api;
+mod panel;
+mod storage;
 mod wifi_config;
 
+use config::AppConfig;
 use dht_embedded::{Dht22, DhtSensor, NoopInterruptControl};
-use edp_display::EdpDisplay;
 
 use esp_idf_svc::hal::{
     delay::Delay,
     gpio::{AnyInputPin, AnyOutputPin, PinDriver},
     modem::Modem,
+    peripheral::Peripheral,
 };
-use http_client::{get_http_client, setup_wifi};
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::sntp::{EspSntp, SyncStatus};
+use esp_idf_svc::wifi::{BlockingWifi, EspWifi};
+use http_client::{connect_wifi, get_http_client, setup_wifi, EspHttpClient};
+use panel::EpdPanel;
 
 use anyhow::{Ok, Result};
 use esp_idf_svc::hal::peripherals::Peripherals;
 
-use weather_api::WeatherApi;
+use storage::NvsStorage;
+use weather_core::dashboard::{Dashboard, WeatherSource};
+use weather_core::edp_display::EdpDisplay;
+use weather_core::http::HttpClient;
+use weather_core::last_weather::LastWeather;
+use weather_core::model::{DateTime, Language, SpecialWeatherTip};
+use weather_core::retry::{RandomSource, Retry, RetryDelay, RetryPolicy};
+use weather_core::scheduler::{is_clock_set, unix_time};
+use weather_core::screen::Freshness;
+use weather_core::storage::{load_last_weather, save_last_weather, Storage};
+use weather_core::text_layout::paginate;
+
+/// How long each page of new warning statements and weather tips stays on screen
+const TEXT_PAGE_DURATION_MS: u32 = 15_000;
+
+/// How long to wait for SNTP after connecting before fetching the weather anyway
+const SNTP_WAIT_SECS: u32 = 10;
+
+type Display<'a> = EdpDisplay<EpdPanel<'a>>;
+
+type DeviceRetry = Retry<BlockingDelay, EspRandom>;
+
+/// Blocks the task between retries
+struct BlockingDelay(Delay);
+
+impl RetryDelay for BlockingDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+/// The hardware random number generator, random once WiFi is up
+struct EspRandom;
+
+impl RandomSource for EspRandom {
+    fn next_u32(&mut self) -> u32 {
+        unsafe { esp_idf_sys::esp_random() }
+    }
+}
 
 fn main() -> Result<()> {
     // It is necessary to call this function once. Otherwise some patches to the runtime
@@ -30,6 +73,8 @@ fn main() -> Result<()> {
     let delay = Delay::default();
 
     let peripheral = Peripherals::take().unwrap();
+    let nvs = EspDefaultNvsPartition::take()?;
+    let config = AppConfig::load(nvs.clone())?;
 
     let senser_pin = peripheral.pins.gpio21;
 
@@ -43,7 +88,7 @@ fn main() -> Result<()> {
     let mut dc: AnyOutputPin = peripheral.pins.gpio27.into();
     let mut rst: AnyOutputPin = peripheral.pins.gpio26.into();
 
-    let mut display = EdpDisplay::new(
+    let panel = EpdPanel::new(
         peripheral.spi3,
         &mut sclk,
         &mut sdo,
@@ -52,12 +97,71 @@ fn main() -> Result<()> {
         &mut dc,
         &mut rst,
     );
+    let mut display = EdpDisplay::new(panel);
+
+    display.set_language(config.weather_api.language);
+    display.set_stale_after(config.stale_after);
+
+    let mut storage = NvsStorage::new(nvs.clone())?;
+    // Something to look at while connecting, replaced once a fetch succeeds. Its age shows up
+    // with a sidebar redraw once SNTP has set the clock.
+    if let Some(last_weather) = load_last_weather(&mut storage) {
+        display_last_weather(&mut display, &config, &last_weather);
+    }
 
     let mut modem = peripheral.modem;
-    dispaly_weather(&mut modem, &mut display)?;
+    // Set up with the first connection, the room temperature keeps showing while it fails
+    let mut wifi = None;
+    let mut connection = None;
+    let mut connect_failures = 0;
+
+    let mut retry = Retry::new(
+        RetryPolicy::default(),
+        BlockingDelay(Delay::default()),
+        EspRandom,
+    );
+    let mut dashboard = None;
+    // Outlives its dashboard, so a withdrawn tip isn't followed by the older ones it replaced
+    let mut shown_tip = None;
+    let mut next_refresh = 0;
 
     let mut count: u16 = 0;
     loop {
+        let now = unix_time();
+        if now >= next_refresh && connection.is_none() {
+            match connect(&mut wifi, &mut modem, &nvs, &config) {
+                std::result::Result::Ok(connected) => {
+                    connection = Some(connected);
+                    connect_failures = 0;
+                }
+                Err(err) => {
+                    connect_failures += 1;
+                    next_refresh = config
+                        .refresh_schedule
+                        .next_reconnect(now, connect_failures);
+                    log::error!("Failed to connect, attempt {}: {:?}", connect_failures, err);
+                }
+            }
+        }
+
+        if let Some(connection) = &mut connection {
+            if now >= next_refresh {
+                next_refresh = config.refresh_schedule.next_after(now);
+                if refresh_weather(
+                    &mut connection.source,
+                    &mut retry,
+                    &config,
+                    &mut display,
+                    &mut storage,
+                    &mut dashboard,
+                    &mut shown_tip,
+                ) {
+                    // The full refresh cleared the room temperature, draw it with its frame again
+                    count = 0;
+                }
+            }
+        }
+
         match sensor.read() {
             std::result::Result::Ok(reading) => {
                 display.display_current_temperature(
@@ -88,15 +192,184 @@ fn main() -> Result<()> {
     }
 }
 
-fn dispaly_weather(modem: &mut Modem, display: &mut EdpDisplay) -> Result<()> {
-    let wifi = setup_wifi(modem)?;
-    let client = get_http_client();
-    let mut api = WeatherApi::new(client);
-    let forcase = api.fetch_local_weather_forecast()?;
-    // let current_weather = api.fetch_current_weather()?;
+/// Kept alive for as long as the weather is refreshed
+struct Connection {
+    /// Sets the clock the refresh schedule is aligned with
+    _sntp: EspSntp<'static>,
+    source: WeatherSource<EspHttpClient>,
+}
+
+/// Retried from the main loop until it succeeds, the WiFi driver is kept once it was set up
+fn connect(
+    wifi: &mut Option<BlockingWifi<EspWifi<'static>>>,
+    modem: &mut Modem,
+    nvs: &EspDefaultNvsPartition,
+    config: &AppConfig,
+) -> Result<Connection> {
+    let wifi = match wifi {
+        Some(wifi) => wifi,
+        None => {
+            // Only one driver has the modem, as none is kept after a failed setup
+            let modem = unsafe { modem.clone_unchecked() };
+            wifi.insert(setup_wifi(modem, nvs.clone())?)
+        }
+    };
+    connect_wifi(wifi)?;
+    let sntp = EspSntp::new_default()?;
+    wait_for_sntp(&sntp);
+    let client = EspHttpClient::new(get_http_client()?, config.max_body_bytes);
+
+    Ok(Connection {
+        _sntp: sntp,
+        source: config.weather_source(client),
+    })
+}
+
+/// The clock dates stored weather and aligns the refresh schedule, but neither is worth
+/// holding up the first fetch for long
+fn wait_for_sntp(sntp: &EspSntp) {
+    for _ in 0..SNTP_WAIT_SECS {
+        if sntp.get_sync_status() == SyncStatus::Completed {
+            return;
+        }
+        Delay::default().delay_ms(1000);
+    }
+
+    log::warn!("SNTP hasn't synchronised, the clock isn't set yet");
+}
+
+/// Fetch the weather and redraw when it changed, returns whether the screen was redrawn
+fn refresh_weather<C: HttpClient>(
+    source: &mut WeatherSource<C>,
+    retry: &mut DeviceRetry,
+    config: &AppConfig,
+    display: &mut Display,
+    storage: &mut impl Storage,
+    dashboard: &mut Option<Dashboard>,
+    shown_tip: &mut Option<SpecialWeatherTip>,
+) -> bool {
+    let latest = match source.fetch(
+        retry,
+        config.show_forecast_text,
+        config.show_current_weather,
+        shown_tip.as_ref(),
+    ) {
+        std::result::Result::Ok(latest) => latest,
+        Err(err) => {
+            log::error!("Failed to refresh the weather: {}", err);
+            return false;
+        }
+    };
+
+    if latest.tip.is_some() {
+        shown_tip.clone_from(&latest.tip);
+    }
+
+    let now = unix_time();
+    let fetched_time =
+        is_clock_set(now).then(|| DateTime::from_unix_time(now, config.utc_offset_minutes()));
+    let freshness = Freshness::Fetched(fetched_time);
+
+    // Every write wears the flash, an unchanged forecast keeps the time it was first saved
+    let weather_changed = dashboard.as_ref().map_or(true, |previous| {
+        previous.forecasts != latest.forecasts || previous.current_weather != latest.current_weather
+    });
+    if weather_changed {
+        let last_weather = LastWeather {
+            saved_at: now,
+            forecasts: latest.forecasts.clone(),
+            current_weather: latest.current_weather.clone(),
+        };
+        if let Err(err) = save_last_weather(storage, &last_weather) {
+            log::warn!("Failed to save the weather: {:?}", err);
+        }
+    }
+
+    if dashboard.as_ref().map(Dashboard::view) == Some(latest.view()) {
+        log::info!("Weather unchanged, keeping the screen");
+        display.set_freshness(freshness);
+        return false;
+    }
+
+    let language = config.weather_api.language;
+    if let Err(err) = display_dashboard(display, language, &latest, dashboard.as_ref(), freshness) {
+        log::error!("Failed to display the weather: {:?}", err);
+    }
+
+    *dashboard = Some(latest);
+    true
+}
 
-    display.display_weather(&forcase)?;
-    // Result<(Vec<WeatherForecast>, WeatherReport)>
+fn display_last_weather(display: &mut Display, config: &AppConfig, last_weather: &LastWeather) {
+    let freshness = Freshness::Stored(last_weather.saved_time(config.utc_offset_minutes()));
+    // Saved before the current weather was turned off in the settings
+    let current_weather = last_weather
+        .current_weather
+        .as_ref()
+        .filter(|_| config.show_current_weather);
+
+    if let Err(err) = display.display_weather(
+        &last_weather.forecasts,
+        current_weather,
+        &[],
+        None,
+        None,
+        freshness,
+    ) {
+        log::error!("Failed to display the last weather: {:?}", err);
+    }
+}
+
+/// Warning statements and tips are paged through only when they are new
+fn display_dashboard(
+    display: &mut Display,
+    language: Language,
+    latest: &Dashboard,
+    previous: Option<&Dashboard>,
+    freshness: Freshness,
+) -> Result<()> {
+    let statements_changed = previous.map_or(true, |previous| {
+        previous.view().warning_statements != latest.view().warning_statements
+    });
+    if !latest.warning_statements.is_empty() && statements_changed {
+        let paragraphs: Vec<String> = latest
+            .warning_statements
+            .iter()
+            .flat_map(|statement| statement.contents.iter().cloned())
+            .collect();
+
+        let title = language.pick(("WARNING", "警告", "警告"));
+        display_text_pages(display, title, &paragraphs)?;
+    }
+
+    if let Some(tip) = &latest.tip {
+        if previous.map_or(true, |previous| previous.tip.as_ref() != Some(tip)) {
+            let title = language.pick(("WEATHER TIP", "天氣提示", "天气提示"));
+            display_text_pages(display, title, std::slice::from_ref(&tip.desc))?;
+        }
+    }
+
+    display.display_weather(
+        &latest.forecasts,
+        latest.current_weather.as_ref(),
+        &latest.warnings,
+        latest.tip.as_ref(),
+        latest.forecast_text.as_ref(),
+        freshness,
+    )
+}
+
+fn display_text_pages(display: &mut Display, title: &str, paragraphs: &[String]) -> Result<()> {
+    let pages = paginate(
+        paragraphs,
+        display.text_page_columns(),
+        display.text_page_lines(),
+    );
+
+    for (i, page) in pages.iter().enumerate() {
+        display.display_text_page(title, page, i, pages.len())?;
+        Delay::default().delay_ms(TEXT_PAGE_DURATION_MS);
+    }
 
     Ok(())
 }
@@ -0,0 +1,349 @@
+use std::convert::Infallible;
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use epd_waveshare::color::{Color, TriColor};
+use epd_waveshare::graphics::DisplayRotation;
+use epd_waveshare::prelude::WaveshareDisplay;
+use esp_idf_svc::hal::gpio::{AnyInputPin, AnyOutputPin};
+use esp_idf_svc::hal::peripheral::Peripheral;
+use esp_idf_svc::hal::spi::SpiAnyPins;
+use esp_idf_svc::hal::{
+    delay::Ets,
+    gpio::{AnyIOPin, Input, Output, PinDriver},
+    spi::{
+        config::{Config, DriverConfig},
+        SpiDeviceDriver, SpiDriver,
+    },
+    units::FromValueType,
+};
+use weather_core::panel::{native_area, window, ColorCapability, Panel};
+
+#[cfg(not(any(
+    feature = "epd2in9b",
+    feature = "epd2in13",
+    feature = "epd4in2",
+    feature = "epd7in5"
+)))]
+compile_error!(
+    "Select the e-paper panel with one of the epd2in9b, epd2in13, epd4in2 or epd7in5 features"
+);
+
+/// How a tri-color drawing shows in the colors of a panel
+trait FromTriColor {
+    fn from_tri_color(color: TriColor) -> Self;
+}
+
+impl FromTriColor for TriColor {
+    fn from_tri_color(color: TriColor) -> Self {
+        color
+    }
+}
+
+impl FromTriColor for Color {
+    fn from_tri_color(color: TriColor) -> Self {
+        match color {
+            TriColor::White => Color::White,
+            TriColor::Black | TriColor::Chromatic => Color::Black,
+        }
+    }
+}
+
+type PanelSpi<'a> = SpiDeviceDriver<'a, SpiDriver<'a>>;
+
+type PanelEpd<'a> = model::Epd<
+    PanelSpi<'a>,
+    PinDriver<'a, AnyInputPin, Input>,
+    PinDriver<'a, AnyOutputPin, Output>,
+    PinDriver<'a, AnyOutputPin, Output>,
+    Ets,
+>;
+
+/// 2.9" black, white and red, drawn landscape
+#[cfg(feature = "epd2in9b")]
+mod model {
+    use epd_waveshare::prelude::WaveshareThreeColorDisplay;
+
+    use super::*;
+
+    pub use epd_waveshare::epd2in9b_v4::{Display2in9b as Display, Epd2in9b as Epd, HEIGHT, WIDTH};
+
+    pub const COLOR_CAPABILITY: ColorCapability = ColorCapability::TriColor;
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+
+    pub fn black_plane(display: &Display) -> &[u8] {
+        display.bw_buffer()
+    }
+
+    /// Also the base the partial updates of the fork are drawn against
+    pub fn full_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+    ) -> anyhow::Result<()> {
+        let delay = &mut Ets;
+
+        epd.update_color_frame(spi, delay, display.bw_buffer(), display.chromatic_buffer())?;
+        epd.update_and_display_frame_base(
+            spi,
+            display.bw_buffer(),
+            Some(display.chromatic_buffer()),
+            delay,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn partial_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+        areas: &[Rectangle],
+    ) -> anyhow::Result<()> {
+        update_partial_frames(epd, spi, black_plane(display), areas)?;
+        epd.display_frame_partial(spi, &mut Ets)?;
+
+        Ok(())
+    }
+}
+
+/// 2.13" black and white, drawn landscape
+#[cfg(all(feature = "epd2in13", not(feature = "epd2in9b")))]
+mod model {
+    use super::*;
+
+    pub use epd_waveshare::epd2in13_v2::{Display2in13 as Display, Epd2in13 as Epd, HEIGHT, WIDTH};
+
+    pub const COLOR_CAPABILITY: ColorCapability = ColorCapability::BlackWhite;
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+
+    pub fn black_plane(display: &Display) -> &[u8] {
+        display.buffer()
+    }
+
+    pub fn full_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+    ) -> anyhow::Result<()> {
+        epd.update_and_display_frame(spi, display.buffer(), &mut Ets)?;
+
+        Ok(())
+    }
+
+    pub fn partial_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+        areas: &[Rectangle],
+    ) -> anyhow::Result<()> {
+        update_partial_frames(epd, spi, black_plane(display), areas)?;
+        epd.display_frame(spi, &mut Ets)?;
+
+        Ok(())
+    }
+}
+
+/// 4.2" black and white
+#[cfg(all(
+    feature = "epd4in2",
+    not(any(feature = "epd2in9b", feature = "epd2in13"))
+))]
+mod model {
+    use super::*;
+
+    pub use epd_waveshare::epd4in2::{Display4in2 as Display, Epd4in2 as Epd, HEIGHT, WIDTH};
+
+    pub const COLOR_CAPABILITY: ColorCapability = ColorCapability::BlackWhite;
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate0;
+
+    pub fn black_plane(display: &Display) -> &[u8] {
+        display.buffer()
+    }
+
+    pub fn full_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+    ) -> anyhow::Result<()> {
+        epd.update_and_display_frame(spi, display.buffer(), &mut Ets)?;
+
+        Ok(())
+    }
+
+    pub fn partial_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+        areas: &[Rectangle],
+    ) -> anyhow::Result<()> {
+        update_partial_frames(epd, spi, black_plane(display), areas)?;
+        epd.display_frame(spi, &mut Ets)?;
+
+        Ok(())
+    }
+}
+
+/// 7.5" black and white, without partial updates
+#[cfg(all(
+    feature = "epd7in5",
+    not(any(feature = "epd2in9b", feature = "epd2in13", feature = "epd4in2"))
+))]
+mod model {
+    use super::*;
+
+    pub use epd_waveshare::epd7in5_v2::{Display7in5 as Display, Epd7in5 as Epd, HEIGHT, WIDTH};
+
+    pub const COLOR_CAPABILITY: ColorCapability = ColorCapability::BlackWhite;
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate0;
+
+    pub fn full_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+    ) -> anyhow::Result<()> {
+        epd.update_and_display_frame(spi, display.buffer(), &mut Ets)?;
+
+        Ok(())
+    }
+
+    pub fn partial_update<'a>(
+        epd: &mut PanelEpd<'a>,
+        spi: &mut PanelSpi<'a>,
+        display: &Display,
+        _areas: &[Rectangle],
+    ) -> anyhow::Result<()> {
+        full_update(epd, spi, display)
+    }
+}
+
+/// Send the pixels of each area in `plane` to the panel's memory, without refreshing it. Unused
+/// by the 7.5" panel, which always refreshes in full.
+#[cfg_attr(
+    all(
+        feature = "epd7in5",
+        not(any(feature = "epd2in9b", feature = "epd2in13", feature = "epd4in2"))
+    ),
+    allow(dead_code)
+)]
+fn update_partial_frames<'a>(
+    epd: &mut PanelEpd<'a>,
+    spi: &mut PanelSpi<'a>,
+    plane: &[u8],
+    areas: &[Rectangle],
+) -> anyhow::Result<()> {
+    for &area in areas {
+        let area = native_area(area, model::ROTATION, model::WIDTH, model::HEIGHT);
+
+        epd.update_partial_frame(
+            spi,
+            &mut Ets,
+            &window(plane, model::WIDTH, area),
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+            area.size.width,
+            area.size.height,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The panel chosen by cargo feature, on SPI
+pub struct EpdPanel<'a> {
+    spi: PanelSpi<'a>,
+    epd: PanelEpd<'a>,
+    display: model::Display,
+}
+
+impl EpdPanel<'_> {
+    pub fn new<'a, SPI: SpiAnyPins>(
+        spi: impl Peripheral<P = SPI> + 'a,
+        sclk: &'a mut AnyOutputPin,
+        sdo: &'a mut AnyOutputPin,
+        cs: &'a mut AnyOutputPin,
+        busy: &'a mut AnyInputPin,
+        dc: &'a mut AnyOutputPin,
+        rst: &'a mut AnyOutputPin,
+    ) -> EpdPanel<'a> {
+        let bus_config = DriverConfig::new();
+        let config = Config::new().baudrate(10u32.MHz().into());
+
+        let mut spi = SpiDeviceDriver::new_single(
+            spi,
+            sclk,
+            sdo,
+            Option::<AnyIOPin>::None,
+            Some(cs),
+            &bus_config,
+            &config,
+        )
+        .unwrap();
+
+        let busy = PinDriver::input(busy).unwrap();
+        let dc = PinDriver::output(dc).unwrap();
+        let rst = PinDriver::output(rst).unwrap();
+        let mut delay = Ets;
+
+        let epd = model::Epd::new(&mut spi, busy, dc, rst, &mut delay, Some(50_000)).unwrap();
+        let mut display = model::Display::default();
+        display.set_rotation(model::ROTATION);
+
+        EpdPanel { spi, epd, display }
+    }
+}
+
+impl OriginDimensions for EpdPanel<'_> {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+impl DrawTarget for EpdPanel<'_> {
+    type Color = TriColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        _ = self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, FromTriColor::from_tri_color(color))),
+        );
+
+        Ok(())
+    }
+}
+
+impl Panel for EpdPanel<'_> {
+    type UpdateError = anyhow::Error;
+
+    fn color_capability(&self) -> ColorCapability {
+        model::COLOR_CAPABILITY
+    }
+
+    fn init(&mut self) {
+        _ = self.clear(TriColor::White);
+    }
+
+    fn full_update(&mut self) -> anyhow::Result<()> {
+        model::full_update(&mut self.epd, &mut self.spi, &self.display)
+    }
+
+    fn partial_update(&mut self, areas: &[Rectangle]) -> anyhow::Result<()> {
+        model::partial_update(&mut self.epd, &mut self.spi, &self.display, areas)
+    }
+
+    fn sleep(&mut self) -> anyhow::Result<()> {
+        self.epd.sleep(&mut self.spi, &mut Ets)?;
+
+        Ok(())
+    }
+
+    fn wake_up(&mut self) -> anyhow::Result<()> {
+        self.epd.wake_up(&mut self.spi, &mut Ets)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use esp_idf_svc::nvs::{EspDefaultNvs, EspDefaultNvsPartition};
+use weather_core::storage::Storage;
+
+/// NVS namespace for data kept across reboots, apart from the settings
+const STORAGE_NAMESPACE: &str = "storage";
+
+/// Blobs in their own NVS namespace
+pub struct NvsStorage {
+    nvs: EspDefaultNvs,
+}
+
+impl NvsStorage {
+    pub fn new(nvs: EspDefaultNvsPartition) -> anyhow::Result<Self> {
+        Ok(Self {
+            nvs: EspDefaultNvs::new(nvs, STORAGE_NAMESPACE, true)?,
+        })
+    }
+}
+
+impl Storage for NvsStorage {
+    type Error = anyhow::Error;
+
+    fn load(&mut self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some(len) = self.nvs.blob_len(key)? else {
+            return Ok(None);
+        };
+
+        let mut buffer = vec![0; len];
+        Ok(self.nvs.get_blob(key, &mut buffer)?.map(<[u8]>::to_vec))
+    }
+
+    fn save(&mut self, key: &str, value: &[u8]) -> anyhow::Result<()> {
+        self.nvs.set_blob(key, value)?;
+        Ok(())
+    }
+}
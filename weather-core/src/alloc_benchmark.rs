@@ -0,0 +1,167 @@
+//! Peak heap use of parsing a 9-day forecast, read from the body as a string versus streamed
+//! into the typed structs. Run on the host with
+//! `cargo test -p weather-core alloc_benchmark -- --nocapture` to see the numbers.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::{self, Read};
+
+use crate::hko_response::NineDayForecastResponse;
+use crate::json::{parse_json, parse_json_reader, LimitedReader, READ_BUFFER_BYTES};
+
+/// Counts the bytes allocated on the current thread, so parallel tests don't interfere
+struct PeakAllocator;
+
+thread_local! {
+    static CURRENT: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record_alloc(size: usize) {
+    let _ = CURRENT.try_with(|current| {
+        current.set(current.get() + size);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(current.get())));
+    });
+}
+
+fn record_dealloc(size: usize) {
+    let _ = CURRENT.try_with(|current| current.set(current.get().saturating_sub(size)));
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// Bytes allocated at most while `f` ran, above what was allocated before
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> usize {
+    let base = CURRENT.with(Cell::get);
+    PEAK.with(|peak| peak.set(base));
+
+    let result = f();
+    let peak = PEAK.with(Cell::get);
+    drop(result);
+
+    peak - base
+}
+
+/// Hands out the body in pieces no larger than the connection returns them
+struct ChunkedBody<'a> {
+    body: &'a [u8],
+}
+
+impl Read for ChunkedBody<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(READ_BUFFER_BYTES).min(self.body.len());
+        buf[..len].copy_from_slice(&self.body[..len]);
+        self.body = &self.body[len..];
+
+        Ok(len)
+    }
+}
+
+/// A 9-day forecast padded to the size of a Chinese one, with the optional fields filled in
+fn nine_day_forecast() -> String {
+    let days: Vec<String> = (0..9)
+        .map(|day| {
+            format!(
+                r#"{{
+                "forecastDate": "202405{:02}",
+                "week": "Monday",
+                "forecastWind": "South to southwest force 3 to 4, occasionally force 5 offshore.",
+                "forecastWeather": "{}",
+                "forecastMaxtemp": {{"value": 30, "unit": "C"}},
+                "forecastMintemp": {{"value": 26, "unit": "C"}},
+                "forecastMaxrh": {{"value": 95, "unit": "percent"}},
+                "forecastMinrh": {{"value": 70, "unit": "percent"}},
+                "ForecastIcon": 54,
+                "PSR": "Medium"
+            }}"#,
+                20 + day,
+                "Mainly cloudy with a few showers and isolated thunderstorms. ".repeat(20),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{
+        "generalSituation": "{}",
+        "weatherForecast": [{}],
+        "updateTime": "2024-05-20T11:30:00+08:00",
+        "seaTemp": {{"place": "North Point", "value": 27, "unit": "C",
+            "recordTime": "2024-05-20T07:00:00+08:00"}},
+        "soilTemp": [{{"place": "Hong Kong Observatory", "value": 28.2, "unit": "C",
+            "recordTime": "2024-05-20T07:00:00+08:00", "depth": {{"unit": "metre", "value": 0.5}}}}]
+    }}"#,
+        "An area of low pressure will bring showers to the coast of Guangdong. ".repeat(40),
+        days.join(",")
+    )
+}
+
+/// The body read into a string first, as `HttpClient::get_request` does
+fn buffered(mut body: impl Read) -> NineDayForecastResponse {
+    let mut bytes = Vec::new();
+    body.read_to_end(&mut bytes).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+
+    parse_json(&text).unwrap()
+}
+
+/// The body read into a string and parsed into a `serde_json::Value` that is then copied,
+/// as the forecast was fetched originally
+fn value_tree(mut body: impl Read) -> NineDayForecastResponse {
+    let mut bytes = Vec::new();
+    body.read_to_end(&mut bytes).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    serde_json::from_value(json.to_owned()).unwrap()
+}
+
+/// Deserialized while it is read, as `EspHttpClient::get_json` does
+fn streamed(body: impl Read) -> NineDayForecastResponse {
+    let reader = io::BufReader::with_capacity(READ_BUFFER_BYTES, body);
+    parse_json_reader(LimitedReader::new(reader, usize::MAX)).unwrap()
+}
+
+#[test]
+fn streaming_allocates_least() {
+    let json = nine_day_forecast();
+    let body = || ChunkedBody {
+        body: json.as_bytes(),
+    };
+
+    let value_tree = peak_allocation(|| value_tree(body()));
+    let buffered = peak_allocation(|| buffered(body()));
+    let streamed = peak_allocation(|| streamed(body()));
+
+    println!("9-day forecast of {} bytes, peak allocation:", json.len());
+    println!("  value tree  {:>7} bytes", value_tree);
+    println!("  buffered    {:>7} bytes", buffered);
+    println!("  streamed    {:>7} bytes", streamed);
+
+    assert!(streamed < buffered, "{} >= {}", streamed, buffered);
+    assert!(buffered < value_tree, "{} >= {}", buffered, value_tree);
+}
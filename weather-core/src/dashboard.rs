@@ -0,0 +1,292 @@
+use crate::http::HttpClient;
+use crate::met_norway_api::MetNorwayApi;
+use crate::model::*;
+use crate::open_meteo_api::OpenMeteoApi;
+use crate::retry::{RandomSource, Retry, RetryDelay};
+use crate::weather_api::WeatherApi;
+use crate::weather_provider::WeatherProvider;
+
+/// Everything fetched in one refresh, its `view` is compared with the previous one to skip
+/// redraws
+#[derive(Clone, Debug, Default)]
+pub struct Dashboard {
+    pub forecasts: Vec<WeatherForecast>,
+    pub current_weather: Option<WeatherReport>,
+    pub warnings: Vec<WarningSignal>,
+    pub warning_statements: Vec<WarningStatement>,
+    pub tip: Option<SpecialWeatherTip>,
+    pub forecast_text: Option<LocalForecastText>,
+}
+
+/// What of a `Dashboard` ends up on screen, without the update times and texts that aren't
+/// drawn, which change without changing anything shown
+#[derive(Debug, PartialEq)]
+pub struct DashboardView<'a> {
+    pub forecasts: &'a [WeatherForecast],
+    pub current_weather: Option<&'a WeatherReport>,
+    pub warnings: &'a [WarningSignal],
+    /// Paged through as text
+    pub warning_statements: Vec<&'a [String]>,
+    pub tip: Option<&'a str>,
+    pub forecast_desc: Option<&'a str>,
+}
+
+impl Dashboard {
+    pub fn view(&self) -> DashboardView<'_> {
+        DashboardView {
+            forecasts: &self.forecasts,
+            current_weather: self.current_weather.as_ref(),
+            warnings: &self.warnings,
+            warning_statements: self
+                .warning_statements
+                .iter()
+                .map(|statement| statement.contents.as_slice())
+                .collect(),
+            tip: self.tip.as_ref().map(|tip| tip.desc.as_str()),
+            forecast_desc: self
+                .forecast_text
+                .as_ref()
+                .map(|text| text.forecast_desc.as_str()),
+        }
+    }
+}
+
+/// The configured provider, kept between refreshes
+pub enum WeatherSource<C: HttpClient> {
+    Hko(WeatherApi<C>),
+    OpenMeteo(OpenMeteoApi<C>),
+    MetNorway(MetNorwayApi<C>),
+}
+
+impl<C> WeatherSource<C>
+where
+    C: HttpClient,
+{
+    /// Only the forecast is required, anything else that fails is logged and left out.
+    /// `shown_tip` is the last tip put on screen, if any.
+    pub fn fetch<D, R>(
+        &mut self,
+        retry: &mut Retry<D, R>,
+        show_forecast_text: bool,
+        show_current_weather: bool,
+        shown_tip: Option<&SpecialWeatherTip>,
+    ) -> Result<Dashboard, ApiError>
+    where
+        D: RetryDelay,
+        R: RandomSource,
+    {
+        match self {
+            Self::Hko(api) => fetch_hko(
+                api,
+                retry,
+                show_forecast_text,
+                show_current_weather,
+                shown_tip,
+            ),
+            Self::OpenMeteo(api) => fetch_forecast(api, retry, show_current_weather),
+            Self::MetNorway(api) => fetch_forecast(api, retry, show_current_weather),
+        }
+    }
+}
+
+/// `None` without a request when it isn't shown
+fn current_weather<P: WeatherProvider>(provider: &mut P, show: bool) -> Option<WeatherReport> {
+    if !show {
+        return None;
+    }
+
+    provider
+        .fetch_current_weather()
+        .map_err(|err| log::warn!("Failed to fetch current weather: {:?}", err))
+        .ok()
+}
+
+/// Forecast and current weather, for providers without warnings or forecast text
+fn fetch_forecast<P, D, R>(
+    provider: &mut P,
+    retry: &mut Retry<D, R>,
+    show_current_weather: bool,
+) -> Result<Dashboard, ApiError>
+where
+    P: WeatherProvider,
+    D: RetryDelay,
+    R: RandomSource,
+{
+    let forecasts = retry.call(|| provider.fetch_forecast())?;
+
+    Ok(Dashboard {
+        forecasts,
+        current_weather: current_weather(provider, show_current_weather),
+        ..Dashboard::default()
+    })
+}
+
+fn fetch_hko<C, D, R>(
+    api: &mut WeatherApi<C>,
+    retry: &mut Retry<D, R>,
+    show_forecast_text: bool,
+    show_current_weather: bool,
+    shown_tip: Option<&SpecialWeatherTip>,
+) -> Result<Dashboard, ApiError>
+where
+    C: HttpClient,
+    D: RetryDelay,
+    R: RandomSource,
+{
+    let forecast = retry.call(|| api.fetch_nine_day_forecast())?;
+    let warnings = api.fetch_warning_summary().unwrap_or_else(|err| {
+        log::warn!("Failed to fetch warning summary: {:?}", err);
+        Vec::new()
+    });
+
+    let warning_statements = if warnings.is_empty() {
+        Vec::new()
+    } else {
+        api.fetch_warning_info().unwrap_or_else(|err| {
+            log::warn!("Failed to fetch warning info: {:?}", err);
+            Vec::new()
+        })
+    };
+
+    let tip = match api.fetch_special_weather_tips() {
+        Ok(tips) => latest_tip(tips, shown_tip),
+        Err(err) => {
+            log::warn!("Failed to fetch special weather tips: {:?}", err);
+            None
+        }
+    };
+
+    let forecast_text = if show_forecast_text {
+        api.fetch_local_forecast_text()
+            .map_err(|err| log::warn!("Failed to fetch local forecast: {:?}", err))
+            .ok()
+    } else {
+        None
+    };
+
+    Ok(Dashboard {
+        forecasts: forecast.forecasts,
+        current_weather: current_weather(api, show_current_weather),
+        warnings,
+        warning_statements,
+        tip,
+        forecast_text,
+    })
+}
+
+/// The newest tip in force, however long before the forecast it was issued, unless it is older
+/// than the one on screen, which would bring back a tip that was already replaced
+fn latest_tip(
+    tips: Vec<SpecialWeatherTip>,
+    shown_tip: Option<&SpecialWeatherTip>,
+) -> Option<SpecialWeatherTip> {
+    tips.into_iter()
+        .filter(|tip| shown_tip.map_or(true, |shown| !shown.is_newer_than(&tip.update_time)))
+        .max_by_key(|tip| tip.update_time.unix_time())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(time: &str) -> DateTime {
+        DateTime::parse(time).unwrap()
+    }
+
+    fn dashboard(update_time: &str) -> Dashboard {
+        Dashboard {
+            forecasts: vec![WeatherForecast {
+                date: 20,
+                week: String::from("MON"),
+                ..WeatherForecast::default()
+            }],
+            warning_statements: vec![WarningStatement {
+                code: String::from("WHOT"),
+                signal: Some(WarningSignal::VeryHot),
+                contents: vec![String::from(
+                    "The Very Hot Weather Warning is now in force.",
+                )],
+                update_time: time(update_time),
+            }],
+            tip: Some(SpecialWeatherTip {
+                desc: String::from("Temperatures will rise to 35 degrees."),
+                update_time: time(update_time),
+            }),
+            forecast_text: Some(LocalForecastText {
+                general_situation: format!("Issued at {}", update_time),
+                tc_info: String::new(),
+                fire_danger_warning: String::new(),
+                forecast_period: String::from("Weather forecast for today"),
+                forecast_desc: String::from("Sunny and very hot."),
+                outlook: format!("Outlook issued at {}", update_time),
+                update_time: time(update_time),
+            }),
+            ..Dashboard::default()
+        }
+    }
+
+    #[test]
+    fn texts_that_arent_drawn_dont_change_the_view() {
+        let earlier = dashboard("2024-07-20T11:00:00+08:00");
+        let later = dashboard("2024-07-20T12:00:00+08:00");
+
+        assert_eq!(earlier.view(), later.view());
+    }
+
+    #[test]
+    fn drawn_fields_change_the_view() {
+        let earlier = dashboard("2024-07-20T11:00:00+08:00");
+
+        let mut later = earlier.clone();
+        later.forecast_text.as_mut().unwrap().forecast_desc = String::from("Hot.");
+        assert_ne!(earlier.view(), later.view());
+
+        let mut later = earlier.clone();
+        later.warning_statements[0]
+            .contents
+            .push(String::from("Drink water."));
+        assert_ne!(earlier.view(), later.view());
+
+        let mut later = earlier.clone();
+        later.forecasts[0].max_temp = 35;
+        assert_ne!(earlier.view(), later.view());
+    }
+
+    fn tip(desc: &str, update_time: &str) -> SpecialWeatherTip {
+        SpecialWeatherTip {
+            desc: String::from(desc),
+            update_time: time(update_time),
+        }
+    }
+
+    #[test]
+    fn tips_in_force_are_shown_after_boot() {
+        let tips = vec![
+            tip("Hot", "2024-07-20T09:00:00+08:00"),
+            tip("Very hot", "2024-07-20T10:00:00+08:00"),
+        ];
+
+        assert_eq!(
+            latest_tip(tips, None).map(|tip| tip.desc),
+            Some(String::from("Very hot"))
+        );
+    }
+
+    #[test]
+    fn the_shown_tip_is_only_replaced_by_a_newer_one() {
+        let shown = tip("Very hot", "2024-07-20T10:00:00+08:00");
+
+        let tips = vec![tip("Hot", "2024-07-20T09:00:00+08:00"), shown.clone()];
+        assert_eq!(latest_tip(tips, Some(&shown)), Some(shown.clone()));
+
+        // Withdrawn, the tip it replaced doesn't come back
+        let tips = vec![tip("Hot", "2024-07-20T09:00:00+08:00")];
+        assert_eq!(latest_tip(tips, Some(&shown)), None);
+
+        let tips = vec![shown.clone(), tip("Cooler", "2024-07-20T11:00:00+08:00")];
+        assert_eq!(
+            latest_tip(tips, Some(&shown)).map(|tip| tip.desc),
+            Some(String::from("Cooler"))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use embedded_graphics::prelude::*;
+
+use crate::model::{
+    Language, LocalForecastText, SpecialWeatherTip, WarningSignal, WeatherForecast, WeatherReport,
+};
+use crate::panel::Panel;
+use crate::scheduler::unix_time;
+use crate::screen::{self, Freshness, Screen};
+
+/// The screens drawn onto a panel and sent to it, with partial updates for the readings
+pub struct EdpDisplay<P> {
+    panel: P,
+    screen: Screen,
+}
+
+impl<P: Panel> EdpDisplay<P> {
+    pub fn new(mut panel: P) -> Self {
+        let size = panel.size();
+        log::info!(
+            "{}x{} {:?} panel",
+            size.width,
+            size.height,
+            panel.color_capability()
+        );
+        panel.init();
+
+        EdpDisplay {
+            panel,
+            screen: Screen::new(size),
+        }
+    }
+
+    /// Language of the texts that will be drawn, Chinese needs taller lines
+    pub fn set_language(&mut self, language: Language) {
+        self.screen.set_language(language);
+    }
+
+    /// Shown with the next redraw of the sidebar, for weather fetched again but unchanged
+    pub fn set_freshness(&mut self, freshness: Freshness) {
+        self.screen.set_freshness(freshness);
+    }
+
+    /// Age after which fetched weather is marked as old
+    pub fn set_stale_after(&mut self, stale_after: Duration) {
+        self.screen.set_stale_after(stale_after);
+    }
+
+    /// Characters per line of `display_text_page`
+    pub fn text_page_columns(&self) -> usize {
+        self.screen.text_page_columns()
+    }
+
+    /// Lines per page of `display_text_page`
+    pub fn text_page_lines(&self) -> usize {
+        self.screen.text_page_lines()
+    }
+
+    /// The forecast after `current_weather`, which is left out when `None`
+    pub fn display_weather(
+        &mut self,
+        weather_forcast: &[WeatherForecast],
+        current_weather: Option<&WeatherReport>,
+        warnings: &[WarningSignal],
+        tip: Option<&SpecialWeatherTip>,
+        forecast_text: Option<&LocalForecastText>,
+        freshness: Freshness,
+    ) -> Result<(), P::UpdateError> {
+        self.screen.set_current_weather(current_weather);
+        self.screen.draw_weather(
+            &mut self.panel,
+            weather_forcast,
+            warnings,
+            tip,
+            forecast_text,
+            freshness,
+        );
+
+        self.panel.full_update()?;
+
+        Ok(())
+    }
+
+    /// Full screen text with a title bar, `lines` should be wrapped to `text_page_columns`
+    pub fn display_text_page(
+        &mut self,
+        title: &str,
+        lines: &[String],
+        page: usize,
+        page_count: usize,
+    ) -> Result<(), P::UpdateError> {
+        self.screen
+            .draw_text_page(&mut self.panel, title, lines, page, page_count);
+
+        self.panel.full_update()?;
+
+        Ok(())
+    }
+
+    pub fn display_current_temperature(
+        &mut self,
+        temperature: f32,
+        humidity: f32,
+        partial_update: bool,
+    ) {
+        if !partial_update {
+            self.screen.draw_sidebar(&mut self.panel, unix_time());
+
+            _ = self.panel.full_update();
+        } else {
+            let temp_text = format!("{:.1}C", temperature);
+            let humidity_text = format!("{:.1}%", humidity);
+
+            log::info!("temp: {temp_text}");
+
+            let temperature_area = self.screen.temperature_reading();
+            let humidity_area = self.screen.humidity_reading();
+
+            screen::draw_reading(
+                &mut self.panel.translated(temperature_area.top_left),
+                &temp_text,
+                self.screen.sidebar_scale(),
+            );
+            screen::draw_reading(
+                &mut self.panel.translated(humidity_area.top_left),
+                &humidity_text,
+                self.screen.sidebar_scale(),
+            );
+
+            _ = self
+                .panel
+                .partial_update(&[temperature_area, humidity_area]);
+        }
+    }
+
+    pub fn sleep(&mut self) {
+        _ = self.panel.sleep();
+    }
+
+    pub fn wake_up(&mut self) {
+        _ = self.panel.wake_up();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel::{ColorCapability, MockPanel, PanelCall};
+    use epd_waveshare::color::TriColor;
+
+    fn display() -> EdpDisplay<MockPanel> {
+        EdpDisplay::new(MockPanel::new(
+            Size::new(296, 128),
+            ColorCapability::TriColor,
+        ))
+    }
+
+    #[test]
+    fn weather_and_sidebar_are_full_updates() {
+        let mut display = display();
+
+        display
+            .display_weather(&[], None, &[], None, None, Freshness::Fetched(None))
+            .unwrap();
+        display.display_current_temperature(27.5, 68.0, false);
+
+        assert_eq!(
+            display.panel.calls,
+            [
+                PanelCall::Init,
+                PanelCall::FullUpdate,
+                PanelCall::FullUpdate
+            ]
+        );
+        assert_eq!(
+            display.panel.frame.pixel(Point::new(290, 2)),
+            TriColor::Black
+        );
+    }
+
+    #[test]
+    fn readings_are_partial_updates() {
+        let mut display = display();
+
+        display.display_current_temperature(27.5, 68.0, true);
+
+        let temperature = display.screen.temperature_reading();
+        let humidity = display.screen.humidity_reading();
+        assert_eq!(
+            display.panel.calls.last(),
+            Some(&PanelCall::PartialUpdate(vec![temperature, humidity]))
+        );
+
+        // White text on the sidebar's black
+        let frame = &display.panel.frame;
+        assert_eq!(frame.pixel(temperature.top_left), TriColor::Black);
+        assert!(temperature
+            .points()
+            .any(|point| frame.pixel(point) == TriColor::White));
+    }
+
+    #[test]
+    fn sleep_and_wake_up() {
+        let mut display = display();
+
+        display.sleep();
+        display.wake_up();
+
+        assert_eq!(
+            display.panel.calls,
+            [PanelCall::Init, PanelCall::Sleep, PanelCall::WakeUp]
+        );
+    }
+}
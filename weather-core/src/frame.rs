@@ -0,0 +1,158 @@
+use alloc::{format, vec, vec::Vec};
+use core::convert::Infallible;
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use epd_waveshare::color::TriColor;
+
+/// One of the two images a tri-color panel is sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plane {
+    Black,
+    Chromatic,
+}
+
+/// Tri-color pixels in the orientation they are drawn, one bit per pixel in each plane like
+/// the panel's own buffers, so layouts can be looked at off the device
+pub struct TriColorFrame {
+    size: Size,
+    black: Vec<u8>,
+    chromatic: Vec<u8>,
+}
+
+impl TriColorFrame {
+    /// All white
+    pub fn new(size: Size) -> Self {
+        let len = (size.width as usize + 7) / 8 * size.height as usize;
+
+        Self {
+            size,
+            black: vec![0; len],
+            chromatic: vec![0; len],
+        }
+    }
+
+    /// Byte and bit of a pixel in either plane, `None` outside the frame
+    fn bit(&self, point: Point) -> Option<(usize, u8)> {
+        let (x, y) = (
+            usize::try_from(point.x).ok()?,
+            usize::try_from(point.y).ok()?,
+        );
+        if x >= self.size.width as usize || y >= self.size.height as usize {
+            return None;
+        }
+
+        let row_bytes = (self.size.width as usize + 7) / 8;
+        Some((y * row_bytes + x / 8, 0x80 >> (x % 8)))
+    }
+
+    /// White outside the frame. Chromatic is printed over black, as on the panel.
+    pub fn pixel(&self, point: Point) -> TriColor {
+        match self.bit(point) {
+            Some((index, mask)) if self.chromatic[index] & mask != 0 => TriColor::Chromatic,
+            Some((index, mask)) if self.black[index] & mask != 0 => TriColor::Black,
+            _ => TriColor::White,
+        }
+    }
+
+    /// Binary PBM of a plane, set pixels are black
+    pub fn to_pbm(&self, plane: Plane) -> Vec<u8> {
+        let bits = match plane {
+            Plane::Black => &self.black,
+            Plane::Chromatic => &self.chromatic,
+        };
+
+        let mut pbm = format!("P4\n{} {}\n", self.size.width, self.size.height).into_bytes();
+        pbm.extend_from_slice(bits);
+        pbm
+    }
+
+    /// Binary PPM of both planes together, with `chromatic` for the third color of the panel
+    pub fn to_ppm(&self, chromatic: Rgb888) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.size.width, self.size.height).into_bytes();
+
+        for y in 0..self.size.height as i32 {
+            for x in 0..self.size.width as i32 {
+                let color = match self.pixel(Point::new(x, y)) {
+                    TriColor::Black => Rgb888::BLACK,
+                    TriColor::White => Rgb888::WHITE,
+                    TriColor::Chromatic => chromatic,
+                };
+                ppm.extend_from_slice(&[color.r(), color.g(), color.b()]);
+            }
+        }
+
+        ppm
+    }
+}
+
+impl OriginDimensions for TriColorFrame {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for TriColorFrame {
+    type Color = TriColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let Some((index, mask)) = self.bit(point) else {
+                continue;
+            };
+
+            let (black, chromatic) = match color {
+                TriColor::Black => (true, false),
+                TriColor::White => (false, false),
+                TriColor::Chromatic => (false, true),
+            };
+
+            if black {
+                self.black[index] |= mask;
+            } else {
+                self.black[index] &= !mask;
+            }
+            if chromatic {
+                self.chromatic[index] |= mask;
+            } else {
+                self.chromatic[index] &= !mask;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::primitives::Rectangle;
+
+    #[test]
+    fn planes_follow_the_last_color() {
+        let mut frame = TriColorFrame::new(Size::new(10, 2));
+        _ = frame.fill_solid(
+            &Rectangle::new(Point::zero(), Size::new(10, 1)),
+            TriColor::Black,
+        );
+        _ = frame.fill_solid(
+            &Rectangle::new(Point::new(8, 0), Size::new(2, 2)),
+            TriColor::Chromatic,
+        );
+
+        assert_eq!(frame.pixel(Point::new(0, 0)), TriColor::Black);
+        assert_eq!(frame.pixel(Point::new(9, 0)), TriColor::Chromatic);
+        assert_eq!(frame.pixel(Point::new(0, 1)), TriColor::White);
+        assert_eq!(frame.pixel(Point::new(10, 0)), TriColor::White);
+
+        // Rows are padded to whole bytes
+        assert_eq!(frame.to_pbm(Plane::Black), b"P4\n10 2\n\xff\x00\x00\x00");
+        assert_eq!(
+            frame.to_pbm(Plane::Chromatic),
+            b"P4\n10 2\n\x00\xc0\x00\xc0"
+        );
+    }
+}
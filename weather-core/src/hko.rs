@@ -0,0 +1,506 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+
+use crate::hko_response::{
+    CurrentWeatherResponse, DailyForecast, LocalForecastResponse, NineDayForecastResponse,
+    WarningDetail, WarningSummaryResponse, WeatherTip,
+};
+use crate::model::*;
+
+impl Weather {
+    pub fn from_icon_code(code: u8) -> Self {
+        match code {
+            50 => Self::Sunny,
+            51 => Self::SunnyPeriods,
+            52 => Self::SunnyIntervals,
+            53 => Self::SunnyPeriodsWithShowers,
+            54 => Self::SunnyIntervalsWithShowers,
+            60 => Self::Cloudly,
+            61 => Self::Overcast,
+            62 => Self::LightRain,
+            63 => Self::Rain,
+            64 => Self::HeavyRain,
+            65 => Self::Thunderstorm,
+            70..=75 => Self::FineNight,
+            76 => Self::MainlyCloudyNight,
+            77 => Self::MainlyFineNight,
+            80 => Self::Windy,
+            81 => Self::Dry,
+            82 => Self::Humid,
+            83 => Self::Fog,
+            84 => Self::Mist,
+            85 => Self::Haze,
+            90 => Self::Hot,
+            91 => Self::Warm,
+            92 => Self::Cool,
+            93 => Self::Cold,
+            _ => Self::Unknow,
+        }
+    }
+}
+
+impl WarningSignal {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let signal = match code {
+            "TC1" => Self::TropicalCyclone(TyphoonSignal::Standby),
+            "TC3" => Self::TropicalCyclone(TyphoonSignal::Strong),
+            "TC8NE" => Self::TropicalCyclone(TyphoonSignal::GaleNorthEast),
+            "TC8SE" => Self::TropicalCyclone(TyphoonSignal::GaleSouthEast),
+            "TC8SW" => Self::TropicalCyclone(TyphoonSignal::GaleSouthWest),
+            "TC8NW" => Self::TropicalCyclone(TyphoonSignal::GaleNorthWest),
+            "TC9" => Self::TropicalCyclone(TyphoonSignal::IncreasingGale),
+            "TC10" => Self::TropicalCyclone(TyphoonSignal::Hurricane),
+            "WRAINA" => Self::RainstormAmber,
+            "WRAINR" => Self::RainstormRed,
+            "WRAINB" => Self::RainstormBlack,
+            "WTS" => Self::Thunderstorm,
+            "WL" => Self::Landslip,
+            "WFNTSA" => Self::NorthernNewTerritoriesFlooding,
+            "WTMW" => Self::Tsunami,
+            "WMSGNL" => Self::StrongMonsoon,
+            "WHOT" => Self::VeryHot,
+            "WCOLD" => Self::Cold,
+            "WFROST" => Self::Frost,
+            "WFIRER" => Self::FireDangerRed,
+            "WFIREY" => Self::FireDangerYellow,
+            _ => return None,
+        };
+
+        Some(signal)
+    }
+}
+
+impl Language {
+    /// Value of HKO's `lang` query parameter
+    pub fn api_code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::TraditionalChinese => "tc",
+            Self::SimplifiedChinese => "sc",
+        }
+    }
+
+    pub fn from_api_code(code: &str) -> Option<Self> {
+        match code {
+            "en" => Some(Self::English),
+            "tc" => Some(Self::TraditionalChinese),
+            "sc" => Some(Self::SimplifiedChinese),
+            _ => None,
+        }
+    }
+}
+
+impl Psr {
+    pub fn from_label(label: &str) -> Self {
+        match label {
+            "High" | "高" => Self::High,
+            "Medium High" | "中高" => Self::MediumHigh,
+            "Medium" | "中" => Self::Medium,
+            "Medium Low" | "中低" => Self::MediumLow,
+            "Low" | "低" => Self::Low,
+            _ => Self::Unknow,
+        }
+    }
+}
+
+/// "Monday" becomes "MON" and "星期一" becomes "一"
+fn short_weekday(week: &str) -> String {
+    match week.strip_prefix("星期") {
+        Some(day) => day.to_owned(),
+        None => week.chars().take(3).collect::<String>().to_uppercase(),
+    }
+}
+
+impl From<DailyForecast> for WeatherForecast {
+    fn from(w: DailyForecast) -> Self {
+        WeatherForecast {
+            max_temp: w.forecast_maxtemp.value,
+            min_temp: w.forecast_mintemp.value,
+            week: short_weekday(&w.week),
+            date: w.forecast_date.day,
+            weather: Weather::from_icon_code(w.forecast_icon),
+            wind: w.forecast_wind,
+            weather_text: w.forecast_weather,
+            max_humidity: w.forecast_maxrh.value,
+            min_humidity: w.forecast_minrh.value,
+            psr: Psr::from_label(&w.psr),
+        }
+    }
+}
+
+impl From<WarningDetail> for WarningStatement {
+    fn from(detail: WarningDetail) -> Self {
+        let signal_code = detail
+            .subtype
+            .as_deref()
+            .unwrap_or(&detail.warning_statement_code);
+
+        WarningStatement {
+            signal: WarningSignal::from_code(signal_code),
+            code: detail.warning_statement_code,
+            contents: detail.contents,
+            update_time: detail.update_time,
+        }
+    }
+}
+
+impl From<LocalForecastResponse> for LocalForecastText {
+    fn from(response: LocalForecastResponse) -> Self {
+        LocalForecastText {
+            general_situation: response.general_situation,
+            tc_info: response.tc_info,
+            fire_danger_warning: response.fire_danger_warning,
+            forecast_period: response.forecast_period,
+            forecast_desc: response.forecast_desc,
+            outlook: response.outlook,
+            update_time: response.update_time,
+        }
+    }
+}
+
+/// An empty forecast is `ApiError::EmptyResponse`
+impl TryFrom<NineDayForecastResponse> for NineDayForecast {
+    type Error = ApiError;
+
+    fn try_from(response: NineDayForecastResponse) -> Result<Self, ApiError> {
+        if response.weather_forecast.is_empty() {
+            return Err(ApiError::EmptyResponse);
+        }
+
+        Ok(NineDayForecast {
+            general_situation: response.general_situation,
+            update_time: response.update_time,
+            forecasts: response
+                .weather_forecast
+                .into_iter()
+                .map(WeatherForecast::from)
+                .collect(),
+            sea_temp: response.sea_temp.map(|t| SeaTemperature {
+                place: t.place,
+                temp: t.value,
+                record_time: t.record_time,
+            }),
+            soil_temps: response
+                .soil_temp
+                .into_iter()
+                .map(|t| SoilTemperature {
+                    place: t.place,
+                    temp: t.value,
+                    depth: t.depth.value,
+                    record_time: t.record_time,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<WeatherTip> for SpecialWeatherTip {
+    fn from(tip: WeatherTip) -> Self {
+        SpecialWeatherTip {
+            desc: tip.desc,
+            update_time: tip.update_time,
+        }
+    }
+}
+
+/// Readings for the station and district named in the response's language
+pub fn current_weather(
+    response: CurrentWeatherResponse,
+    station: &str,
+    district: &str,
+) -> Result<WeatherReport, ApiError> {
+    let temperatures = response.temperature.data;
+
+    if temperatures.is_empty() {
+        return Err(ApiError::EmptyResponse);
+    }
+
+    let temperature = temperatures
+        .iter()
+        .find(|t| t.place == station)
+        .ok_or_else(|| ApiError::UnknownStation {
+            name: station.to_owned(),
+            available: temperatures.iter().map(|t| t.place.to_owned()).collect(),
+        })?;
+
+    let rainfall = response
+        .rainfall
+        .data
+        .iter()
+        .find(|r| r.place == district)
+        .ok_or_else(|| ApiError::UnknownRainfallDistrict {
+            name: district.to_owned(),
+            available: response
+                .rainfall
+                .data
+                .iter()
+                .map(|r| r.place.to_owned())
+                .collect(),
+        })?;
+
+    let icon_code = response.icon.first().copied().unwrap_or(60);
+
+    Ok(WeatherReport {
+        place: temperature.place.to_owned(),
+        temp: temperature.value,
+        weather: Weather::from_icon_code(icon_code),
+        update_time: response.update_time,
+        rainfall: rainfall.max,
+    })
+}
+
+/// Warnings currently in force, most important first
+pub fn warning_signals(response: &WarningSummaryResponse) -> Vec<WarningSignal> {
+    let mut warnings: Vec<WarningSignal> = response
+        .values()
+        .filter(|w| w.action_code != "CANCEL")
+        .filter_map(|w| {
+            let signal = WarningSignal::from_code(&w.code);
+            if signal.is_none() {
+                log::warn!("Unknown warning code {}", w.code);
+            }
+            signal
+        })
+        .collect();
+    warnings.sort();
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt;
+
+    use super::*;
+    use crate::hko_response::NineDayForecastResponse;
+    use crate::json::parse_json;
+
+    /// Icon codes listed in HKO's weather icon table
+    const DOCUMENTED_ICON_CODES: [u8; 29] = [
+        50, 51, 52, 53, 54, 60, 61, 62, 63, 64, 65, 70, 71, 72, 73, 74, 75, 76, 77, 80, 81, 82, 83,
+        84, 85, 90, 91, 92, 93,
+    ];
+
+    #[test]
+    fn every_documented_icon_code_is_known() {
+        for code in DOCUMENTED_ICON_CODES {
+            assert_ne!(
+                Weather::from_icon_code(code),
+                Weather::Unknow,
+                "icon {}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn undocumented_icon_code_is_unknown() {
+        assert_eq!(Weather::from_icon_code(0), Weather::Unknow);
+        assert_eq!(Weather::from_icon_code(55), Weather::Unknow);
+    }
+
+    const FND_FIXTURE: &str = include_str!("../tests/fixtures/hko/fnd.json");
+    const RHRREAD_FIXTURE: &str = include_str!("../tests/fixtures/hko/rhrread.json");
+    const WARNSUM_FIXTURE: &str = include_str!("../tests/fixtures/hko/warnsum.json");
+
+    fn nine_day_forecast(json: &str) -> Result<NineDayForecast, ApiError> {
+        parse_json::<NineDayForecastResponse>(json)?.try_into()
+    }
+
+    fn current_weather_from(json: &str) -> Result<WeatherReport, ApiError> {
+        current_weather(parse_json(json)?, "Sham Shui Po", "Sham Shui Po")
+    }
+
+    fn warnings_from(json: &str) -> Result<Vec<WarningSignal>, ApiError> {
+        Ok(warning_signals(&parse_json(json)?))
+    }
+
+    /// `fixture` with `edit` applied
+    fn edited(fixture: &str, edit: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut json = serde_json::from_str(fixture).unwrap();
+        edit(&mut json);
+        json.to_string()
+    }
+
+    fn assert_invalid_field<T: fmt::Debug>(result: Result<T, ApiError>, expected: &str) {
+        match result {
+            Err(ApiError::InvalidField { path, .. }) => assert_eq!(path, expected),
+            other => panic!("expected an invalid {}, got {:?}", expected, other),
+        }
+    }
+
+    #[test]
+    fn fnd_fixture() {
+        let forecast = nine_day_forecast(FND_FIXTURE).unwrap();
+        assert_eq!(
+            forecast.update_time.to_string(),
+            "2024-05-20T11:30:00+08:00"
+        );
+        assert_eq!(forecast.forecasts.len(), 9);
+
+        let first = &forecast.forecasts[0];
+        assert_eq!((first.date, first.week.as_str()), (21, "TUE"));
+        assert_eq!((first.min_temp, first.max_temp), (25, 29));
+        assert_eq!((first.min_humidity, first.max_humidity), (80, 95));
+        assert_eq!(first.weather, Weather::Rain);
+        assert_eq!(first.psr, Psr::High);
+        assert_eq!(forecast.forecasts[8].date, 29);
+        assert_eq!(forecast.forecasts[8].psr, Psr::MediumLow);
+
+        let sea_temp = forecast.sea_temp.unwrap();
+        assert_eq!(
+            (sea_temp.place.as_str(), sea_temp.temp),
+            ("North Point", 27.0)
+        );
+        assert_eq!(forecast.soil_temps.len(), 2);
+        assert_eq!(forecast.soil_temps[1].depth, 1.0);
+    }
+
+    #[test]
+    fn rhrread_fixture() {
+        let report = current_weather_from(RHRREAD_FIXTURE).unwrap();
+
+        assert_eq!(
+            report,
+            WeatherReport {
+                place: String::from("Sham Shui Po"),
+                temp: 25,
+                weather: Weather::Rain,
+                update_time: DateTime::parse("2024-05-20T12:02:00+08:00").unwrap(),
+                rainfall: Some(7),
+            }
+        );
+    }
+
+    #[test]
+    fn rhrread_fixture_district_under_maintenance() {
+        let response = parse_json(RHRREAD_FIXTURE).unwrap();
+        let report = current_weather(response, "Sham Shui Po", "Kwun Tong").unwrap();
+
+        assert_eq!(report.rainfall, None);
+    }
+
+    #[test]
+    fn rhrread_fixture_unknown_station() {
+        let response = parse_json(RHRREAD_FIXTURE).unwrap();
+
+        match current_weather(response, "Sham Shui Po Park", "Sham Shui Po") {
+            Err(ApiError::UnknownStation { name, available }) => {
+                assert_eq!(name, "Sham Shui Po Park");
+                assert_eq!(available.len(), 27);
+                assert!(available.contains(&String::from("Sham Shui Po")));
+            }
+            other => panic!("expected an unknown station, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn warnsum_fixture() {
+        let warnings = warnings_from(WARNSUM_FIXTURE).unwrap();
+
+        // The cancelled T1 is left out
+        assert_eq!(
+            warnings,
+            [
+                WarningSignal::RainstormAmber,
+                WarningSignal::Thunderstorm,
+                WarningSignal::FireDangerYellow
+            ]
+        );
+    }
+
+    #[test]
+    fn warnsum_without_warnings() {
+        let warnings = warnings_from("{}").unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn fnd_missing_weather_forecast() {
+        let json = edited(FND_FIXTURE, |json| {
+            json.as_object_mut().unwrap().remove("weatherForecast");
+        });
+
+        match nine_day_forecast(&json) {
+            Err(ApiError::MissingField { path, .. }) => assert_eq!(path, "weatherForecast"),
+            other => panic!("expected a missing field, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn fnd_non_numeric_temperature() {
+        let json = edited(FND_FIXTURE, |json| {
+            json["weatherForecast"][2]["forecastMintemp"]["value"] = "26".into();
+        });
+
+        assert_invalid_field(
+            nine_day_forecast(&json),
+            "weatherForecast[2].forecastMintemp.value",
+        );
+    }
+
+    #[test]
+    fn fnd_empty_weather_forecast() {
+        let json = edited(FND_FIXTURE, |json| {
+            json["weatherForecast"] = serde_json::json!([]);
+        });
+
+        assert!(matches!(
+            nine_day_forecast(&json),
+            Err(ApiError::EmptyResponse)
+        ));
+    }
+
+    #[test]
+    fn fnd_short_dates() {
+        let json = edited(FND_FIXTURE, |json| {
+            json["weatherForecast"][0]["forecastDate"] = "2024052".into();
+        });
+        assert_invalid_field(nine_day_forecast(&json), "weatherForecast[0].forecastDate");
+
+        let json = edited(FND_FIXTURE, |json| {
+            json["updateTime"] = "2024-05-20".into();
+        });
+        assert_invalid_field(nine_day_forecast(&json), "updateTime");
+    }
+
+    #[test]
+    fn rhrread_non_numeric_temperature() {
+        let json = edited(RHRREAD_FIXTURE, |json| {
+            json["temperature"]["data"][23]["value"] = "N/A".into();
+        });
+
+        assert_invalid_field(current_weather_from(&json), "temperature.data[23].value");
+    }
+
+    #[test]
+    fn rhrread_empty_arrays() {
+        let json = edited(RHRREAD_FIXTURE, |json| {
+            json["temperature"]["data"] = serde_json::json!([]);
+        });
+        assert!(matches!(
+            current_weather_from(&json),
+            Err(ApiError::EmptyResponse)
+        ));
+
+        let json = edited(RHRREAD_FIXTURE, |json| {
+            json["rainfall"]["data"] = serde_json::json!([]);
+        });
+        assert!(matches!(
+            current_weather_from(&json),
+            Err(ApiError::UnknownRainfallDistrict { available, .. }) if available.is_empty()
+        ));
+
+        // No icon falls back to cloudy
+        let json = edited(RHRREAD_FIXTURE, |json| {
+            json["icon"] = serde_json::json!([]);
+        });
+        let report = current_weather_from(&json).unwrap();
+        assert_eq!(report.weather, Weather::Cloudly);
+    }
+
+    #[test]
+    fn warnsum_non_object() {
+        let result = warnings_from("[]");
+        assert_invalid_field(result, "");
+    }
+}
@@ -0,0 +1,175 @@
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::model::DateTime;
+
+/// `dataType=fnd` payload, the 9-day weather forecast
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NineDayForecastResponse {
+    pub general_situation: String,
+    pub weather_forecast: Vec<DailyForecast>,
+    pub update_time: DateTime,
+    pub sea_temp: Option<SeaTemp>,
+    #[serde(default)]
+    pub soil_temp: Vec<SoilTemp>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyForecast {
+    pub forecast_date: ForecastDate,
+    pub week: String,
+    pub forecast_wind: String,
+    pub forecast_weather: String,
+    pub forecast_maxtemp: Measurement<i8>,
+    pub forecast_mintemp: Measurement<i8>,
+    pub forecast_maxrh: Measurement<u8>,
+    pub forecast_minrh: Measurement<u8>,
+    #[serde(rename = "ForecastIcon")]
+    pub forecast_icon: u8,
+    #[serde(rename = "PSR")]
+    pub psr: String,
+}
+
+/// `forecastDate`, published as `YYYYMMDD`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForecastDate {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl ForecastDate {
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.len() != 8 || !text.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let date = Self {
+            year: text[..4].parse().ok()?,
+            month: text[4..6].parse().ok()?,
+            day: text[6..].parse().ok()?,
+        };
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+    }
+}
+
+impl<'de> Deserialize<'de> for ForecastDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Self::parse(&text).ok_or_else(|| de::Error::custom(format!("invalid date {:?}", text)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Measurement<T> {
+    pub value: T,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeaTemp {
+    pub place: String,
+    pub value: f32,
+    pub record_time: DateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoilTemp {
+    pub place: String,
+    pub value: f32,
+    pub record_time: DateTime,
+    pub depth: Measurement<f32>,
+}
+
+/// `dataType=rhrread` payload, the current weather report
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentWeatherResponse {
+    pub temperature: PlaceReadings<i8>,
+    pub rainfall: Rainfall,
+    #[serde(default)]
+    pub icon: Vec<u8>,
+    pub update_time: DateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlaceReadings<T> {
+    pub data: Vec<PlaceReading<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlaceReading<T> {
+    pub place: String,
+    pub value: T,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Rainfall {
+    pub data: Vec<RainfallReading>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RainfallReading {
+    pub place: String,
+    /// Missing while the district's gauges are under maintenance
+    pub max: Option<u16>,
+}
+
+/// `dataType=warnsum` payload, keyed by warning type (`WTCSGNL`, `WRAIN`, ...)
+pub type WarningSummaryResponse = BTreeMap<String, WarningSummary>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarningSummary {
+    pub code: String,
+    /// `ISSUE`, `REISSUE`, `EXTEND`, `UPDATE` or `CANCEL`
+    pub action_code: String,
+}
+
+/// `dataType=warningInfo` payload, empty while no warning is in force
+#[derive(Debug, Deserialize)]
+pub struct WarningInfoResponse {
+    #[serde(default)]
+    pub details: Vec<WarningDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarningDetail {
+    pub contents: Vec<String>,
+    pub warning_statement_code: String,
+    /// Signal code for warnings that have levels, e.g. `TC8NE` or `WRAINA`
+    pub subtype: Option<String>,
+    pub update_time: DateTime,
+}
+
+/// `dataType=swt` payload
+#[derive(Debug, Deserialize)]
+pub struct SpecialWeatherTipsResponse {
+    #[serde(default)]
+    pub swt: Vec<WeatherTip>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherTip {
+    pub desc: String,
+    pub update_time: DateTime,
+}
+
+/// `dataType=flw` payload, the local weather forecast
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalForecastResponse {
+    pub general_situation: String,
+    pub tc_info: String,
+    pub fire_danger_warning: String,
+    pub forecast_period: String,
+    pub forecast_desc: String,
+    pub outlook: String,
+    pub update_time: DateTime,
+}
@@ -0,0 +1,126 @@
+use alloc::{string::String, vec::Vec};
+
+use serde::de::DeserializeOwned;
+
+use crate::json::parse_json;
+use crate::model::ApiError;
+
+/// Response headers an `HttpClient` passes on in `HttpResponse::headers`
+pub const RESPONSE_HEADERS: [&str; 4] = ["Date", "Expires", "Last-Modified", "ETag"];
+
+/// Larger bodies are cut off with `ApiError::BodyTooLarge`, MET Norway's forecast is the
+/// largest at around 40 KB
+pub const DEFAULT_MAX_BODY_BYTES: usize = 128 * 1024;
+
+/// Whatever the server answered, including error statuses. The body is the text, or for
+/// `HttpClient::get_json` the deserialized value of a 2xx response.
+pub struct HttpResponse<B = String> {
+    pub status: u16,
+    /// Those of `RESPONSE_HEADERS` the server sent
+    pub headers: Vec<(String, String)>,
+    pub body: B,
+}
+
+impl<B> HttpResponse<B> {
+    /// Header names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Turn a status outside 2xx into `ApiError::HttpStatus`
+    pub fn error_for_status(self) -> Result<Self, ApiError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(ApiError::HttpStatus(self.status))
+        }
+    }
+}
+
+pub trait HttpClient {
+    /// GET `url` with extra request headers. Every status comes back as a response, it is
+    /// only an error when no response could be read at all.
+    fn get_request(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, ApiError>;
+
+    /// GET `url` and deserialize a 2xx body, other statuses come back without one. Clients
+    /// that can should deserialize while reading rather than buffer the body like this does.
+    fn get_json<T: DeserializeOwned>(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse<Option<T>>, ApiError> {
+        let response = self.get_request(url, headers)?;
+        let body = if response.is_success() {
+            Some(parse_json(&response.body)?)
+        } else {
+            None
+        };
+
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers,
+            body,
+        })
+    }
+}
+
+/// Replies to every request with the same response, for running the parsers against canned JSON
+#[cfg(test)]
+pub struct CannedHttpClient {
+    pub response: HttpResponse,
+    /// Answered in turn before `response`, for failures that pass
+    pub queued: Vec<Result<HttpResponse, ApiError>>,
+    /// URL and headers of every request so far
+    pub requests: Vec<(String, Vec<(String, String)>)>,
+}
+
+#[cfg(test)]
+impl CannedHttpClient {
+    pub fn new(body: &str) -> Self {
+        Self {
+            response: HttpResponse {
+                status: 200,
+                headers: Vec::new(),
+                body: body.to_owned(),
+            },
+            queued: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl HttpClient for CannedHttpClient {
+    fn get_request(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, ApiError> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        self.requests.push((url.to_owned(), headers));
+
+        if !self.queued.is_empty() {
+            return self.queued.remove(0);
+        }
+
+        Ok(HttpResponse {
+            status: self.response.status,
+            headers: self.response.headers.clone(),
+            body: self.response.body.clone(),
+        })
+    }
+}
@@ -0,0 +1,289 @@
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::{borrow::ToOwned, format, string::ToString};
+#[cfg(feature = "std")]
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+
+use crate::model::ApiError;
+
+/// Bytes read from the connection at a time
+pub const READ_BUFFER_BYTES: usize = 256;
+
+/// Deserialize a response body, reporting where in the JSON it went wrong
+pub fn parse_json<T: DeserializeOwned>(json: &str) -> Result<T, ApiError> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = serde_path_to_error::deserialize(&mut deserializer)?;
+    // Only whitespace may follow
+    deserializer.end().map_err(json_error)?;
+
+    Ok(value)
+}
+
+/// Like `parse_json`, but reading the body as it arrives instead of from a copy in memory
+#[cfg(feature = "std")]
+pub fn parse_json_reader<T: DeserializeOwned>(reader: impl io::Read) -> Result<T, ApiError> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let value = serde_path_to_error::deserialize(&mut deserializer)?;
+    // Only whitespace may follow
+    deserializer.end().map_err(json_error)?;
+
+    Ok(value)
+}
+
+/// Marks the body limit inside the `io::Error` of a `LimitedReader`
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct BodyTooLarge(usize);
+
+#[cfg(feature = "std")]
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "body is larger than {} bytes", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BodyTooLarge {}
+
+/// Fails with `ApiError::BodyTooLarge`, through `io::Error`, once more than `limit` bytes
+/// have been read
+#[cfg(feature = "std")]
+pub struct LimitedReader<R> {
+    inner: R,
+    limit: usize,
+    remaining: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read> LimitedReader<R> {
+    pub fn new(inner: R, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            remaining: limit,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read> io::Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            // One more byte tells a body of exactly the limit from a longer one
+            let mut probe = [0_u8; 1];
+            return match self.inner.read(&mut probe)? {
+                0 => Ok(0),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    BodyTooLarge(self.limit),
+                )),
+            };
+        }
+
+        let len = buf.len().min(self.remaining);
+        let read = self.inner.read(&mut buf[..len])?;
+        self.remaining -= read;
+
+        Ok(read)
+    }
+}
+
+/// Readers report timeouts as `io::ErrorKind::TimedOut`
+#[cfg(feature = "std")]
+impl From<io::Error> for ApiError {
+    fn from(err: io::Error) -> Self {
+        let inner = err.get_ref();
+
+        if let Some(too_large) = inner.and_then(|inner| inner.downcast_ref::<BodyTooLarge>()) {
+            return ApiError::BodyTooLarge { limit: too_large.0 };
+        }
+
+        match err.kind() {
+            io::ErrorKind::TimedOut => ApiError::Timeout(Box::new(err)),
+            _ => ApiError::Transport(Box::new(err)),
+        }
+    }
+}
+
+/// A read error while streaming comes back from serde_json as an I/O error
+fn json_error(source: serde_json::Error) -> ApiError {
+    #[cfg(feature = "std")]
+    if source.classify() == Category::Io {
+        return ApiError::from(io::Error::from(source));
+    }
+
+    ApiError::JsonSyntax {
+        line: source.line(),
+        column: source.column(),
+        source,
+    }
+}
+
+/// The field named by a serde "missing field `value`" message, which the path points at the
+/// object of. serde has no error kind for it, so this matches the wording of
+/// `serde::de::Error::missing_field` as of serde 1.0 and serde_json 1.0.117. Should the wording
+/// change, the error falls back to `ApiError::InvalidField` at the object, which
+/// `missing_field_message_of_this_serde_json` catches.
+fn missing_field(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(field, _)| field)
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for ApiError {
+    fn from(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        // The path is "." at the root
+        let path = err.path().to_string();
+        let path = path.trim_start_matches('.').to_owned();
+        let source = err.into_inner();
+
+        if source.classify() != Category::Data {
+            return json_error(source);
+        }
+
+        let missing_field = missing_field(&source.to_string()).map(ToOwned::to_owned);
+
+        match missing_field {
+            Some(field) if path.is_empty() => ApiError::MissingField {
+                path: field,
+                source,
+            },
+            Some(field) => ApiError::MissingField {
+                path: format!("{}.{}", path, field),
+                source,
+            },
+            None => ApiError::InvalidField { path, source },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hko_response::NineDayForecastResponse;
+
+    const FND_FIXTURE: &str = include_str!("../tests/fixtures/hko/fnd.json");
+
+    #[test]
+    fn json_syntax_error_has_position() {
+        let err = parse_json::<NineDayForecastResponse>("{\n  \"generalSituation\": ,\n}")
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err,
+            ApiError::JsonSyntax {
+                line: 2,
+                column: 23,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "invalid JSON at line 2 column 23");
+    }
+
+    #[test]
+    fn missing_field_has_json_path() {
+        let json = FND_FIXTURE.replacen(
+            r#""forecastMaxtemp":{"value":29,"unit":"C"}"#,
+            r#""forecastMaxtemp":{"unit":"C"}"#,
+            1,
+        );
+        let err = parse_json::<NineDayForecastResponse>(&json).err().unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "missing field weatherForecast[0].forecastMaxtemp.value"
+        );
+        #[cfg(feature = "std")]
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn missing_field_message_of_this_serde_json() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Value {
+            #[allow(dead_code)]
+            value: i32,
+        }
+
+        let source = serde_json::from_str::<Value>("{}").unwrap_err();
+
+        assert_eq!(
+            source.to_string(),
+            "missing field `value` at line 1 column 2"
+        );
+        assert_eq!(missing_field(&source.to_string()), Some("value"));
+    }
+
+    #[test]
+    fn other_messages_arent_a_missing_field() {
+        assert_eq!(missing_field("field `value` is missing"), None);
+        assert_eq!(
+            missing_field("invalid type: string \"63\", expected i32"),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_field_has_json_path() {
+        let json = FND_FIXTURE.replacen(r#""ForecastIcon":63"#, r#""ForecastIcon":"63""#, 1);
+        let err = parse_json::<NineDayForecastResponse>(&json).err().unwrap();
+
+        assert!(matches!(
+            &err,
+            ApiError::InvalidField { path, .. } if path == "weatherForecast[0].ForecastIcon"
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn streamed_body_parses_like_buffered() {
+        let json = FND_FIXTURE;
+        let reader = LimitedReader::new(json.as_bytes(), json.len());
+
+        let streamed: NineDayForecastResponse = parse_json_reader(reader).unwrap();
+        let buffered: NineDayForecastResponse = parse_json(json).unwrap();
+
+        assert_eq!(streamed.update_time, buffered.update_time);
+        assert_eq!(streamed.weather_forecast.len(), 9);
+        assert_eq!(
+            streamed.weather_forecast.len(),
+            buffered.weather_forecast.len()
+        );
+        assert_eq!(streamed.soil_temp.len(), buffered.soil_temp.len());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn body_over_limit_is_rejected() {
+        let json = FND_FIXTURE;
+        let reader = LimitedReader::new(json.as_bytes(), json.len() - 1);
+
+        let result: Result<NineDayForecastResponse, _> = parse_json_reader(reader);
+        assert!(matches!(
+            result,
+            Err(ApiError::BodyTooLarge { limit }) if limit == json.len() - 1
+        ));
+    }
+
+    #[test]
+    fn trailing_data_is_a_syntax_error() {
+        let json = format!("{} {{}}", FND_FIXTURE);
+
+        let buffered: Result<NineDayForecastResponse, _> = parse_json(&json);
+        assert!(matches!(buffered, Err(ApiError::JsonSyntax { .. })));
+
+        #[cfg(feature = "std")]
+        {
+            let reader = LimitedReader::new(json.as_bytes(), json.len());
+            let streamed: Result<NineDayForecastResponse, _> = parse_json_reader(reader);
+            assert!(matches!(streamed, Err(ApiError::JsonSyntax { .. })));
+        }
+    }
+}
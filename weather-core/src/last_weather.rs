@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{DateTime, WeatherForecast, WeatherReport};
+use crate::scheduler::is_clock_set;
+
+/// The weather last fetched successfully, shown at boot until a fetch succeeds
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct LastWeather {
+    /// Seconds since the Unix epoch
+    pub saved_at: i64,
+    pub forecasts: Vec<WeatherForecast>,
+    pub current_weather: Option<WeatherReport>,
+}
+
+impl LastWeather {
+    /// What is kept in storage
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Fails on bytes that can't be read back, e.g. after a format change
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// `None` if SNTP hadn't set the clock when this was saved
+    pub fn saved_time(&self, utc_offset_minutes: i16) -> Option<DateTime> {
+        is_clock_set(self.saved_at)
+            .then(|| DateTime::from_unix_time(self.saved_at, utc_offset_minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Psr, Weather};
+    use alloc::{string::String, vec};
+
+    /// 2024-05-20 12:00 HKT
+    const SAVED_AT: i64 = 1_716_177_600;
+
+    fn last_weather() -> LastWeather {
+        LastWeather {
+            saved_at: SAVED_AT,
+            forecasts: vec![WeatherForecast {
+                date: 20,
+                week: String::from("MON"),
+                max_temp: 30,
+                min_temp: 26,
+                weather: Weather::SunnyIntervalsWithShowers,
+                psr: Psr::MediumLow,
+                ..WeatherForecast::default()
+            }],
+            current_weather: Some(WeatherReport {
+                place: String::from("Sham Shui Po"),
+                temp: 28,
+                weather: Weather::Cloudly,
+                update_time: DateTime::parse("2024-05-20T11:00:00+08:00").unwrap(),
+                rainfall: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = last_weather().to_bytes().unwrap();
+
+        assert_eq!(LastWeather::from_bytes(&bytes).unwrap(), last_weather());
+    }
+
+    #[test]
+    fn unreadable_is_an_error() {
+        assert!(LastWeather::from_bytes(br#"{"saved_at": 1716177600}"#).is_err());
+        assert!(LastWeather::from_bytes(b"").is_err());
+    }
+
+    #[test]
+    fn saved_time_needs_a_set_clock() {
+        let saved_time = last_weather().saved_time(480).unwrap();
+        assert_eq!(saved_time.to_string(), "2024-05-20T12:00:00+08:00");
+
+        let saved_before_sync = LastWeather {
+            saved_at: 30,
+            ..last_weather()
+        };
+        assert_eq!(saved_before_sync.saved_time(480), None);
+    }
+}
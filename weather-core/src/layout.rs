@@ -0,0 +1,413 @@
+use alloc::vec::Vec;
+
+use embedded_graphics::{mono_font::MonoFont, prelude::*, primitives::Rectangle};
+
+use crate::text_layout::text_columns;
+
+/// Smallest cell the forecast of a day fits in, the icon beside the date over the temperatures
+pub const MIN_CELL_SIZE: Size = Size::new(74, 60);
+
+/// Width of the sidebar with the indoor readings, on the right of every panel, at scale 1
+pub const SIDEBAR_WIDTH: u32 = 71;
+
+/// The panel the sidebar is drawn at scale 1 on, the 2.9" one
+const SIDEBAR_PANEL_SIZE: Size = Size::new(296, 128);
+
+/// Where something smaller than its area is placed along one axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// Space kept clear inside the edges of an area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Padding {
+    pub const fn all(padding: u32) -> Self {
+        Padding {
+            top: padding,
+            right: padding,
+            bottom: padding,
+            left: padding,
+        }
+    }
+
+    pub const fn left(padding: u32) -> Self {
+        Padding {
+            top: 0,
+            right: 0,
+            bottom: 0,
+            left: padding,
+        }
+    }
+}
+
+/// `area` less `padding`, empty when the padding doesn't fit
+pub fn pad(area: Rectangle, padding: Padding) -> Rectangle {
+    Rectangle::new(
+        area.top_left + Point::new(padding.left as i32, padding.top as i32),
+        Size::new(
+            area.size.width.saturating_sub(padding.left + padding.right),
+            area.size
+                .height
+                .saturating_sub(padding.top + padding.bottom),
+        ),
+    )
+}
+
+/// Offset and length of `count` parts of `length`, the pixels left over go to the first ones
+fn split(length: u32, count: u32) -> impl Iterator<Item = (u32, u32)> {
+    let (part, rest) = match count {
+        0 => (0, 0),
+        _ => (length / count, length % count),
+    };
+
+    (0..count).map(move |i| (i * part + i.min(rest), part + u32::from(i < rest)))
+}
+
+/// `count` columns of `area` side by side, from the left
+pub fn columns(area: Rectangle, count: u32) -> Vec<Rectangle> {
+    split(area.size.width, count)
+        .map(|(x, width)| {
+            Rectangle::new(
+                area.top_left + Point::new(x as i32, 0),
+                Size::new(width, area.size.height),
+            )
+        })
+        .collect()
+}
+
+/// `count` rows of `area` one above the other, from the top
+pub fn rows(area: Rectangle, count: u32) -> Vec<Rectangle> {
+    split(area.size.height, count)
+        .map(|(y, height)| {
+            Rectangle::new(
+                area.top_left + Point::new(0, y as i32),
+                Size::new(area.size.width, height),
+            )
+        })
+        .collect()
+}
+
+/// Offset of something `length` long placed in `available`, the start when it doesn't fit
+fn align_offset(available: u32, length: u32, align: Align) -> i32 {
+    let space = available.saturating_sub(length) as i32;
+
+    match align {
+        Align::Start => 0,
+        Align::Center => space / 2,
+        Align::End => space,
+    }
+}
+
+/// Top-left corner of something `size` big placed in `area`
+pub fn align(area: Rectangle, size: Size, horizontal: Align, vertical: Align) -> Point {
+    area.top_left
+        + Point::new(
+            align_offset(area.size.width, size.width, horizontal),
+            align_offset(area.size.height, size.height, vertical),
+        )
+}
+
+/// Width of `text` drawn in the mono `font`, a CJK character takes two columns of it
+pub fn text_width(text: &str, font: &MonoFont) -> u32 {
+    let advance = font.character_size.width + font.character_spacing;
+
+    (text_columns(text) as u32 * advance).saturating_sub(font.character_spacing)
+}
+
+/// Columns and rows for `days` cells in `area`, `(0, 0)` when not even one fits
+fn grid_shape(area: Size, days: usize) -> (u32, u32) {
+    let max_columns = area.width / MIN_CELL_SIZE.width;
+    let max_rows = area.height / MIN_CELL_SIZE.height;
+    let days = (days as u32).min(max_columns * max_rows);
+
+    let mut best = (0, 0);
+    let mut best_score = (u32::MAX, u64::MAX);
+
+    for rows in 1..=max_rows {
+        let columns = (days + rows - 1) / rows;
+        if columns == 0 || columns > max_columns {
+            continue;
+        }
+
+        // Fewest empty cells, then the cell closest to the proportions of the smallest one
+        let across = (area.width / columns) as u64 * MIN_CELL_SIZE.height as u64;
+        let down = (area.height / rows) as u64 * MIN_CELL_SIZE.width as u64;
+        let score = (
+            columns * rows - days,
+            across.max(down) * 1000 / across.min(down).max(1),
+        );
+
+        if score < best_score {
+            best = (columns, rows);
+            best_score = score;
+        }
+    }
+
+    best
+}
+
+/// Where the parts of the forecast screen go on a panel of a given size
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DashboardLayout {
+    /// Left of the sidebar and the forecast text, split into the cells
+    pub grid: Rectangle,
+    pub columns: u32,
+    pub rows: u32,
+    /// The first cell, when the current weather is shown
+    pub current_weather: Option<Rectangle>,
+    /// One per day shown, by row from the top left
+    pub cells: Vec<Rectangle>,
+    /// Right of the grid when today's description is shown
+    pub forecast_text: Option<Rectangle>,
+    /// Right edge of the panel, as tall as it
+    pub sidebar: Rectangle,
+}
+
+impl DashboardLayout {
+    /// As many of `days` as fit in cells of at least `MIN_CELL_SIZE`, in the shape of grid
+    /// that keeps them closest to its proportions. With `forecast_text` a third of the space
+    /// left of the sidebar is kept for the description, with `current_weather` the first cell
+    /// is kept for the readings ahead of the days.
+    pub fn new(size: Size, days: usize, forecast_text: bool, current_weather: bool) -> Self {
+        let sidebar = sidebar(size);
+        let main = Rectangle::new(
+            Point::zero(),
+            Size::new(sidebar.top_left.x as u32, size.height),
+        );
+
+        let (grid, forecast_text) = if forecast_text {
+            let text_width = (main.size.width / 3)
+                .max(MIN_CELL_SIZE.width)
+                .min(main.size.width);
+            let grid_width = main.size.width - text_width;
+
+            (
+                Rectangle::new(main.top_left, Size::new(grid_width, size.height)),
+                Some(Rectangle::new(
+                    Point::new(grid_width as i32, 0),
+                    Size::new(text_width, size.height),
+                )),
+            )
+        } else {
+            (main, None)
+        };
+
+        let count = days + usize::from(current_weather);
+        let (columns, rows) = grid_shape(grid.size, count);
+        let mut cells: Vec<Rectangle> = self::rows(grid, rows)
+            .into_iter()
+            .flat_map(|row| self::columns(row, columns))
+            .take(count)
+            .collect();
+        let current_weather = if current_weather && !cells.is_empty() {
+            Some(cells.remove(0))
+        } else {
+            None
+        };
+
+        DashboardLayout {
+            grid,
+            columns,
+            rows,
+            current_weather,
+            cells,
+            forecast_text,
+            sidebar,
+        }
+    }
+
+    /// x of the lines between columns of cells
+    pub fn column_separators(&self) -> impl Iterator<Item = i32> {
+        columns(self.grid, self.columns)
+            .into_iter()
+            .skip(1)
+            .map(|column| column.top_left.x)
+    }
+
+    /// y of the lines between rows of cells
+    pub fn row_separators(&self) -> impl Iterator<Item = i32> {
+        rows(self.grid, self.rows)
+            .into_iter()
+            .skip(1)
+            .map(|row| row.top_left.y)
+    }
+}
+
+/// Whole times the content of a cell `size` big is drawn larger than in `MIN_CELL_SIZE`
+pub fn cell_scale(size: Size) -> u32 {
+    (size.width / MIN_CELL_SIZE.width)
+        .min(size.height / MIN_CELL_SIZE.height)
+        .max(1)
+}
+
+/// Whole times the sidebar of a panel `size` big is drawn larger than on the 2.9" one
+pub fn sidebar_scale(size: Size) -> u32 {
+    (size.width / SIDEBAR_PANEL_SIZE.width)
+        .min(size.height / SIDEBAR_PANEL_SIZE.height)
+        .max(1)
+}
+
+/// The sidebar of a panel `size` big, it doesn't depend on what the grid shows
+pub fn sidebar(size: Size) -> Rectangle {
+    let width = (SIDEBAR_WIDTH * sidebar_scale(size)).min(size.width);
+
+    Rectangle::new(
+        Point::new((size.width - width) as i32, 0),
+        Size::new(width, size.height),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_7X13_BOLD};
+
+    fn rectangle(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn leftover_pixels_go_to_the_first_parts() {
+        let area = rectangle(10, 0, 11, 5);
+
+        assert_eq!(
+            columns(area, 3),
+            [
+                rectangle(10, 0, 4, 5),
+                rectangle(14, 0, 4, 5),
+                rectangle(18, 0, 3, 5),
+            ]
+        );
+        assert_eq!(
+            rows(area, 2),
+            [rectangle(10, 0, 11, 3), rectangle(10, 3, 11, 2)]
+        );
+        assert!(columns(area, 0).is_empty());
+    }
+
+    #[test]
+    fn padding_and_alignment() {
+        let area = rectangle(10, 20, 100, 50);
+
+        assert_eq!(pad(area, Padding::all(5)), rectangle(15, 25, 90, 40));
+        assert_eq!(pad(area, Padding::left(3)), rectangle(13, 20, 97, 50));
+        assert_eq!(pad(area, Padding::all(60)).size, Size::zero());
+
+        let size = Size::new(20, 10);
+        assert_eq!(
+            align(area, size, Align::Start, Align::End),
+            Point::new(10, 60)
+        );
+        assert_eq!(
+            align(area, size, Align::Center, Align::Center),
+            Point::new(50, 40)
+        );
+        assert_eq!(
+            align(area, Size::new(200, 10), Align::End, Align::Start),
+            Point::new(10, 20)
+        );
+    }
+
+    #[test]
+    fn text_width_of_mono_fonts() {
+        assert_eq!(text_width("", &FONT_6X10), 0);
+        assert_eq!(text_width("25-29C", &FONT_7X13_BOLD), 42);
+        assert_eq!(text_width("星期一", &FONT_6X10), 36);
+    }
+
+    #[test]
+    fn grid_on_the_2in9_panel() {
+        let layout = DashboardLayout::new(Size::new(296, 128), 9, false, false);
+
+        assert_eq!(layout.sidebar, rectangle(225, 0, 71, 128));
+        assert_eq!((layout.columns, layout.rows), (3, 2));
+        assert_eq!(layout.cells.len(), 6);
+        assert_eq!(layout.cells[4], rectangle(75, 64, 75, 64));
+        assert_eq!(layout.column_separators().collect::<Vec<_>>(), [75, 150]);
+        assert_eq!(layout.row_separators().collect::<Vec<_>>(), [64]);
+
+        let layout = DashboardLayout::new(Size::new(296, 128), 9, true, false);
+
+        assert_eq!(layout.forecast_text, Some(rectangle(150, 0, 75, 128)));
+        assert_eq!((layout.columns, layout.rows), (2, 2));
+    }
+
+    #[test]
+    fn every_day_on_larger_panels() {
+        for size in [Size::new(400, 300), Size::new(800, 480)] {
+            for forecast_text in [false, true] {
+                let layout = DashboardLayout::new(size, 9, forecast_text, false);
+
+                assert_eq!(layout.cells.len(), 9, "{:?}", size);
+                for cell in &layout.cells {
+                    assert!(cell.size.width >= MIN_CELL_SIZE.width);
+                    assert!(cell.size.height >= MIN_CELL_SIZE.height);
+                }
+            }
+        }
+
+        let layout = DashboardLayout::new(Size::new(800, 480), 9, false, false);
+        assert_eq!((layout.columns, layout.rows), (3, 3));
+    }
+
+    #[test]
+    fn content_scales_with_the_panel() {
+        let layout = DashboardLayout::new(Size::new(296, 128), 9, false, false);
+        assert_eq!(sidebar_scale(Size::new(296, 128)), 1);
+        assert_eq!(cell_scale(layout.cells[0].size), 1);
+
+        let layout = DashboardLayout::new(Size::new(400, 300), 9, false, false);
+        assert_eq!(layout.sidebar, rectangle(329, 0, 71, 300));
+        assert_eq!(cell_scale(layout.cells[0].size), 1);
+
+        // Cells of the 7.5" panel are twice the smallest one, and so is the sidebar
+        let layout = DashboardLayout::new(Size::new(800, 480), 9, false, false);
+        assert_eq!(layout.sidebar, rectangle(658, 0, 142, 480));
+        assert_eq!(layout.cells[0], rectangle(0, 0, 220, 160));
+        assert_eq!(cell_scale(layout.cells[0].size), 2);
+
+        assert_eq!(cell_scale(Size::new(10, 10)), 1);
+    }
+
+    #[test]
+    fn short_forecasts_get_larger_cells() {
+        let layout = DashboardLayout::new(Size::new(296, 128), 2, false, false);
+
+        assert_eq!((layout.columns, layout.rows), (2, 1));
+        assert_eq!(
+            layout.cells,
+            [rectangle(0, 0, 113, 128), rectangle(113, 0, 112, 128)]
+        );
+
+        let layout = DashboardLayout::new(Size::new(296, 128), 0, false, false);
+        assert!(layout.cells.is_empty());
+        assert_eq!(layout.row_separators().count(), 0);
+    }
+
+    #[test]
+    fn current_weather_takes_the_first_cell() {
+        let layout = DashboardLayout::new(Size::new(296, 128), 9, false, true);
+
+        assert_eq!(layout.current_weather, Some(rectangle(0, 0, 75, 64)));
+        assert_eq!(layout.cells.len(), 5);
+        assert_eq!(layout.cells[0], rectangle(75, 0, 75, 64));
+
+        let layout = DashboardLayout::new(Size::new(296, 128), 9, true, true);
+
+        assert_eq!(layout.current_weather, Some(rectangle(0, 0, 75, 64)));
+        assert_eq!(layout.cells.len(), 3);
+        for cell in layout.cells.iter().chain(&layout.current_weather) {
+            assert_eq!(cell.intersection(&layout.sidebar).size, Size::zero());
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Weather data, parsing and layout shared by the firmware, free of ESP-IDF so it builds and
+//! tests on the host. `no_std` with `alloc` when the default `std` feature is off. The
+//! `render` feature adds the screen layouts, drawn onto any tri-color `DrawTarget`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(all(test, feature = "std"))]
+mod alloc_benchmark;
+#[cfg(feature = "std")]
+pub mod dashboard;
+#[cfg(all(feature = "render", feature = "std"))]
+pub mod edp_display;
+#[cfg(feature = "render")]
+pub mod frame;
+pub mod hko;
+pub mod hko_response;
+pub mod http;
+pub mod json;
+pub mod last_weather;
+#[cfg(feature = "render")]
+pub mod layout;
+pub mod met_norway;
+#[cfg(feature = "std")]
+pub mod met_norway_api;
+pub mod met_norway_response;
+pub mod model;
+pub mod open_meteo;
+pub mod open_meteo_api;
+pub mod open_meteo_response;
+#[cfg(feature = "render")]
+pub mod panel;
+pub mod retry;
+pub mod scheduler;
+#[cfg(feature = "render")]
+pub mod screen;
+#[cfg(all(test, feature = "render"))]
+mod snapshot;
+pub mod storage;
+pub mod text_layout;
+pub mod weather_api;
+pub mod weather_provider;
@@ -0,0 +1,212 @@
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+
+use crate::met_norway_response::TimeStep;
+use crate::model::*;
+
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+impl Weather {
+    /// Icon for a MET Norway symbol code such as `partlycloudy_night` or `heavyrainandthunder`
+    pub fn from_met_symbol(symbol: &str) -> Self {
+        let (name, variant) = symbol.split_once('_').unwrap_or((symbol, "day"));
+        let is_night = variant == "night";
+
+        match (name, is_night) {
+            ("clearsky", false) => Self::Sunny,
+            ("clearsky", true) => Self::FineNight,
+            ("fair", false) => Self::SunnyPeriods,
+            ("fair", true) => Self::MainlyFineNight,
+            ("partlycloudy", false) => Self::SunnyIntervals,
+            ("partlycloudy", true) => Self::MainlyCloudyNight,
+            ("cloudy", _) => Self::Cloudly,
+            ("fog", _) => Self::Fog,
+            (name, _) if name.contains("thunder") => Self::Thunderstorm,
+            (name, _) if name.contains("sleet") || name.contains("snow") => Self::Cold,
+            ("heavyrain" | "heavyrainshowers", _) => Self::HeavyRain,
+            ("lightrainshowers" | "rainshowers", false) => Self::SunnyIntervalsWithShowers,
+            ("lightrain" | "lightrainshowers", _) => Self::LightRain,
+            ("rain" | "rainshowers", _) => Self::Rain,
+            _ => Self::Unknow,
+        }
+    }
+}
+
+/// "lightrainshowersandthunder_day" becomes "Light rain showers and thunder"
+fn symbol_description(symbol: &str) -> String {
+    const WORDS: [(&str, &str); 13] = [
+        ("clearsky", "clear sky"),
+        ("partlycloudy", "partly cloudy"),
+        ("fair", "fair"),
+        ("cloudy", "cloudy"),
+        ("fog", "fog"),
+        ("light", "light"),
+        ("heavy", "heavy"),
+        ("rain", "rain"),
+        ("sleet", "sleet"),
+        ("snow", "snow"),
+        ("showers", "showers"),
+        ("and", "and"),
+        ("thunder", "thunder"),
+    ];
+
+    let name = symbol_name(symbol);
+    let mut rest = name;
+    let mut words = Vec::new();
+
+    while !rest.is_empty() {
+        let Some((prefix, word)) = WORDS.iter().find(|(prefix, _)| rest.starts_with(prefix)) else {
+            return name.to_owned();
+        };
+        words.push(*word);
+        rest = &rest[prefix.len()..];
+    }
+
+    let text = words.join(" ");
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
+
+/// Symbol code without its `_day`, `_night` or `_polartwilight` variant
+fn symbol_name(symbol: &str) -> &str {
+    symbol.split('_').next().unwrap_or_default()
+}
+
+/// Timeseries times are UTC, days are split at local midnight estimated from the longitude
+pub fn utc_offset(location: &Location) -> i64 {
+    round(location.longitude / 15.0) as i64 * 3600
+}
+
+fn min_max(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+/// The steps of one local day folded together
+#[derive(Default)]
+struct DayAggregate {
+    temperatures: Vec<f32>,
+    humidities: Vec<f32>,
+    max_wind_speed: f32,
+    /// Symbol names with the number of hours they cover
+    symbols: Vec<(String, u32)>,
+}
+
+impl DayAggregate {
+    /// `seconds_into_day` is the local time of the step
+    fn add(&mut self, step: &TimeStep, seconds_into_day: i64) {
+        let details = &step.data.instant.details;
+        self.temperatures.extend(details.air_temperature);
+        // The instants miss the highs and lows between the 6-hourly steps, their periods
+        // have them unless they run into the next day
+        if seconds_into_day + 6 * 3600 <= SECONDS_PER_DAY {
+            if let Some(period) = &step.data.next_6_hours {
+                self.temperatures.extend(period.details.air_temperature_max);
+                self.temperatures.extend(period.details.air_temperature_min);
+            }
+        }
+        self.humidities.extend(details.relative_humidity);
+        self.max_wind_speed = self.max_wind_speed.max(details.wind_speed.unwrap_or(0.0));
+
+        // Hourly steps carry the next hour, the 6-hourly ones further ahead only the next six
+        let period = match (&step.data.next_1_hours, &step.data.next_6_hours) {
+            (Some(period), _) => Some((period, 1)),
+            (None, Some(period)) => Some((period, 6)),
+            (None, None) => None,
+        };
+
+        if let Some((period, hours)) = period {
+            let name = symbol_name(&period.summary.symbol_code);
+            match self.symbols.iter_mut().find(|(symbol, _)| symbol == name) {
+                Some((_, total)) => *total += hours,
+                None => self.symbols.push((name.to_owned(), hours)),
+            }
+        }
+    }
+
+    /// The symbol covering the most hours, the earliest one on a tie
+    fn dominant_symbol(&self) -> Option<&str> {
+        self.symbols
+            .iter()
+            .rev()
+            .max_by_key(|(_, hours)| *hours)
+            .map(|(symbol, _)| symbol.as_str())
+    }
+
+    fn forecast(&self, day: i64) -> WeatherForecast {
+        let (_, _, date) = civil_from_days(day);
+        let (min_temp, max_temp) = min_max(&self.temperatures);
+        let (min_humidity, max_humidity) = min_max(&self.humidities);
+        let symbol = self.dominant_symbol();
+
+        WeatherForecast {
+            date: date as u8,
+            // 1970-01-01 was a Thursday
+            week: WEEKDAYS[(day + 4).rem_euclid(7) as usize].to_owned(),
+            max_temp: round(max_temp) as i8,
+            min_temp: round(min_temp) as i8,
+            weather: symbol.map_or(Weather::Unknow, Weather::from_met_symbol),
+            wind: format!("{:.0} km/h", self.max_wind_speed * 3.6),
+            weather_text: symbol.map(symbol_description).unwrap_or_default(),
+            max_humidity: round(max_humidity) as u8,
+            min_humidity: round(min_humidity) as u8,
+            // The compact format has no precipitation probability
+            psr: Psr::Unknow,
+        }
+    }
+}
+
+pub fn daily_forecasts(timeseries: &[TimeStep], utc_offset: i64) -> Vec<WeatherForecast> {
+    let mut days: Vec<(i64, DayAggregate)> = Vec::new();
+
+    for step in timeseries {
+        let local_time = step.time.unix_time() + utc_offset;
+        let day = local_time.div_euclid(SECONDS_PER_DAY);
+        if days.last().map(|(last, _)| *last) != Some(day) {
+            days.push((day, DayAggregate::default()));
+        }
+
+        if let Some((_, aggregate)) = days.last_mut() {
+            aggregate.add(step, local_time.rem_euclid(SECONDS_PER_DAY));
+        }
+    }
+
+    days.iter()
+        .map(|(day, aggregate)| aggregate.forecast(*day))
+        .collect()
+}
+
+/// Readings of the first step, shown as `place`
+pub fn current_weather(
+    timeseries: &[TimeStep],
+    place: &str,
+    utc_offset: i64,
+) -> Result<WeatherReport, ApiError> {
+    let step = timeseries.first().ok_or(ApiError::EmptyResponse)?;
+    let period = step
+        .data
+        .next_1_hours
+        .as_ref()
+        .or(step.data.next_6_hours.as_ref());
+
+    Ok(WeatherReport {
+        place: place.to_owned(),
+        temp: round(step.data.instant.details.air_temperature.unwrap_or(0.0)) as i8,
+        weather: period.map_or(Weather::Unknow, |p| {
+            Weather::from_met_symbol(&p.summary.symbol_code)
+        }),
+        update_time: DateTime::from_unix_time(step.time.unix_time(), (utc_offset / 60) as i16),
+        rainfall: period
+            .and_then(|p| p.details.precipitation_amount)
+            .map(|mm| round(mm) as u16),
+    })
+}
@@ -0,0 +1,305 @@
+use std::time::{Duration, Instant};
+
+use crate::http::{HttpClient, HttpResponse};
+use crate::met_norway::{self, utc_offset};
+use crate::met_norway_response::LocationForecastResponse;
+use crate::model::*;
+use crate::weather_provider::WeatherProvider;
+
+const MET_NORWAY_API_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0/compact";
+
+/// MET Norway's terms ask every client to identify itself with a way to reach the developer
+pub const DEFAULT_USER_AGENT: &str =
+    "esp-weather-report/0.1 github.com/tomcheung/esp-weather-report";
+
+/// Last downloaded forecast with what is needed to revalidate it
+struct CachedForecast {
+    response: LocationForecastResponse,
+    last_modified: Option<String>,
+    fetched_at: Instant,
+    /// The server asks not to be polled again before this has passed
+    max_age: Duration,
+}
+
+pub struct MetNorwayApi<C: HttpClient> {
+    http_client: C,
+    location: Location,
+    user_agent: String,
+    cache: Option<CachedForecast>,
+}
+
+/// Seconds into the day of "HH:MM:SS"
+fn parse_clock(time: &str) -> Option<i64> {
+    let mut fields = time.split(':').map(|field| field.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (fields.next()??, fields.next()??, fields.next()??);
+
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Seconds since the epoch of an HTTP date such as "Tue, 21 May 2024 10:04:49 GMT"
+fn parse_http_date(date: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let mut parts = date.split_whitespace().skip(1);
+    let day = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|&month| month == month_name)? as u32 + 1;
+    let year = parts.next()?.parse().ok()?;
+    let time = parse_clock(parts.next()?)?;
+
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY + time)
+}
+
+/// How long the server asks not to be polled again. `Expires` is read against the server's
+/// own `Date` as the device clock may not be set.
+fn max_age<B>(response: &HttpResponse<B>) -> Duration {
+    let expires = response.header("Expires").and_then(parse_http_date);
+    let date = response.header("Date").and_then(parse_http_date);
+
+    match (expires, date) {
+        (Some(expires), Some(date)) if expires > date => {
+            Duration::from_secs((expires - date) as u64)
+        }
+        _ => Duration::ZERO,
+    }
+}
+
+impl<C> MetNorwayApi<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, location: Location, user_agent: String) -> Self {
+        Self {
+            http_client: client,
+            location,
+            user_agent,
+            cache: None,
+        }
+    }
+
+    /// Download the forecast unless the cached one has not expired, an expired one is
+    /// revalidated with `If-Modified-Since`
+    fn refresh(&mut self) -> Result<&LocationForecastResponse, ApiError> {
+        let is_fresh = self
+            .cache
+            .as_ref()
+            .is_some_and(|cache| cache.fetched_at.elapsed() < cache.max_age);
+
+        if !is_fresh {
+            let url = format!(
+                "{}?lat={:.4}&lon={:.4}",
+                MET_NORWAY_API_URL, self.location.latitude, self.location.longitude
+            );
+
+            let mut headers = vec![("User-Agent", self.user_agent.as_str())];
+            if let Some(last_modified) = self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.last_modified.as_deref())
+            {
+                headers.push(("If-Modified-Since", last_modified));
+            }
+
+            let response = self.http_client.get_json(&url, &headers)?;
+            let max_age = max_age(&response);
+
+            match (response.status, &mut self.cache) {
+                (304, Some(cache)) => {
+                    cache.fetched_at = Instant::now();
+                    cache.max_age = max_age;
+                }
+                _ => {
+                    let response = response.error_for_status()?;
+                    let last_modified = response.header("Last-Modified").map(str::to_owned);
+
+                    self.cache = Some(CachedForecast {
+                        response: response.body.ok_or(ApiError::EmptyResponse)?,
+                        last_modified,
+                        fetched_at: Instant::now(),
+                        max_age,
+                    });
+                }
+            }
+        }
+
+        self.cache
+            .as_ref()
+            .map(|cache| &cache.response)
+            .ok_or(ApiError::EmptyResponse)
+    }
+}
+
+impl<C> WeatherProvider for MetNorwayApi<C>
+where
+    C: HttpClient,
+{
+    fn fetch_forecast(&mut self) -> Result<Vec<WeatherForecast>, ApiError> {
+        let utc_offset = utc_offset(&self.location);
+        let response = self.refresh()?;
+
+        Ok(met_norway::daily_forecasts(
+            &response.properties.timeseries,
+            utc_offset,
+        ))
+    }
+
+    fn fetch_current_weather(&mut self) -> Result<WeatherReport, ApiError> {
+        let utc_offset = utc_offset(&self.location);
+        let place = self.location.place.to_owned();
+        let response = self.refresh()?;
+
+        met_norway::current_weather(&response.properties.timeseries, &place, utc_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::CannedHttpClient;
+
+    /// Two hourly steps in the evening of 20 May in Hong Kong, then the night into 21 May
+    /// where the series turns 6-hourly. The period of the first step runs past midnight.
+    const LOCATION_FORECAST: &str = r#"{
+        "type": "Feature",
+        "geometry": {"type": "Point", "coordinates": [114.1622, 22.3303, 10]},
+        "properties": {
+            "meta": {"updated_at": "2024-05-20T13:52:10Z", "units": {"air_temperature": "celsius"}},
+            "timeseries": [
+                {"time": "2024-05-20T14:00:00Z", "data": {
+                    "instant": {"details": {"air_temperature": 27.4, "relative_humidity": 84.2, "wind_speed": 3.1}},
+                    "next_1_hours": {"summary": {"symbol_code": "clearsky_night"}, "details": {"precipitation_amount": 0.0}},
+                    "next_6_hours": {"summary": {"symbol_code": "fair_night"}, "details": {"precipitation_amount": 0.0, "air_temperature_max": 31.0, "air_temperature_min": 22.0}}
+                }},
+                {"time": "2024-05-20T15:00:00Z", "data": {
+                    "instant": {"details": {"air_temperature": 26.8, "relative_humidity": 88.0, "wind_speed": 2.5}},
+                    "next_1_hours": {"summary": {"symbol_code": "clearsky_night"}, "details": {"precipitation_amount": 0.0}}
+                }},
+                {"time": "2024-05-20T16:00:00Z", "data": {
+                    "instant": {"details": {"air_temperature": 26.1, "relative_humidity": 90.5, "wind_speed": 2.0}},
+                    "next_1_hours": {"summary": {"symbol_code": "lightrain"}, "details": {"precipitation_amount": 0.4}}
+                }},
+                {"time": "2024-05-20T18:00:00Z", "data": {
+                    "instant": {"details": {"air_temperature": 25.6, "relative_humidity": 93.0, "wind_speed": 1.8}},
+                    "next_6_hours": {"summary": {"symbol_code": "partlycloudy_night"}, "details": {"precipitation_amount": 0.0, "air_temperature_max": 28.9, "air_temperature_min": 24.2}}
+                }}
+            ]
+        }
+    }"#;
+
+    fn api(client: CannedHttpClient) -> MetNorwayApi<CannedHttpClient> {
+        MetNorwayApi::new(client, Location::default(), DEFAULT_USER_AGENT.to_owned())
+    }
+
+    fn header<'a>(request: &'a (String, Vec<(String, String)>), name: &str) -> Option<&'a str> {
+        request
+            .1
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn hourly_steps_fold_into_local_days() {
+        let mut api = api(CannedHttpClient::new(LOCATION_FORECAST));
+
+        let forecasts = api.fetch_forecast().unwrap();
+        assert_eq!(forecasts.len(), 2);
+
+        let today = &forecasts[0];
+        assert_eq!((today.date, today.week.as_str()), (20, "MON"));
+        assert_eq!((today.min_temp, today.max_temp), (27, 27));
+        assert_eq!(today.weather, Weather::Sunny);
+        assert_eq!(today.weather_text, "Clear sky");
+        assert_eq!((today.min_humidity, today.max_humidity), (84, 88));
+
+        // The 6-hourly step outweighs the hour of light rain, and brings its extremes
+        let tomorrow = &forecasts[1];
+        assert_eq!((tomorrow.date, tomorrow.week.as_str()), (21, "TUE"));
+        assert_eq!((tomorrow.min_temp, tomorrow.max_temp), (24, 29));
+        assert_eq!(tomorrow.weather, Weather::SunnyIntervals);
+        assert_eq!(tomorrow.weather_text, "Partly cloudy");
+
+        let request = &api.http_client.requests[0];
+        assert!(request.0.ends_with("?lat=22.3303&lon=114.1622"));
+        assert_eq!(header(request, "User-Agent"), Some(DEFAULT_USER_AGENT));
+    }
+
+    #[test]
+    fn current_weather_from_first_step() {
+        let mut api = api(CannedHttpClient::new(LOCATION_FORECAST));
+
+        let report = api.fetch_current_weather().unwrap();
+        assert_eq!(report.temp, 27);
+        assert_eq!(report.weather, Weather::FineNight);
+        assert_eq!(report.update_time.to_string(), "2024-05-20T22:00:00+08:00");
+        assert_eq!(report.rainfall, Some(0));
+    }
+
+    #[test]
+    fn no_request_before_expires() {
+        let mut client = CannedHttpClient::new(LOCATION_FORECAST);
+        client.response.headers = vec![
+            (
+                "Date".to_owned(),
+                "Mon, 20 May 2024 13:55:00 GMT".to_owned(),
+            ),
+            (
+                "Expires".to_owned(),
+                "Mon, 20 May 2024 14:25:00 GMT".to_owned(),
+            ),
+        ];
+        let mut api = api(client);
+
+        api.fetch_forecast().unwrap();
+        api.fetch_current_weather().unwrap();
+
+        assert_eq!(api.http_client.requests.len(), 1);
+    }
+
+    #[test]
+    fn expired_forecast_is_revalidated() {
+        let mut client = CannedHttpClient::new(LOCATION_FORECAST);
+        client.response.headers = vec![
+            (
+                "Date".to_owned(),
+                "Mon, 20 May 2024 13:55:00 GMT".to_owned(),
+            ),
+            (
+                "Expires".to_owned(),
+                "Mon, 20 May 2024 13:55:00 GMT".to_owned(),
+            ),
+            (
+                "Last-Modified".to_owned(),
+                "Mon, 20 May 2024 13:52:10 GMT".to_owned(),
+            ),
+        ];
+        let mut api = api(client);
+        api.fetch_forecast().unwrap();
+
+        api.http_client.response.status = 304;
+        api.http_client.response.body = String::new();
+        let forecasts = api.fetch_forecast().unwrap();
+
+        assert_eq!(forecasts.len(), 2);
+        assert_eq!(api.http_client.requests.len(), 2);
+        assert_eq!(
+            header(&api.http_client.requests[0], "If-Modified-Since"),
+            None
+        );
+        assert_eq!(
+            header(&api.http_client.requests[1], "If-Modified-Since"),
+            Some("Mon, 20 May 2024 13:52:10 GMT")
+        );
+    }
+
+    #[test]
+    fn http_date() {
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:01:40 GMT"), Some(100));
+        assert_eq!(
+            parse_http_date("Tue, 21 May 2024 10:04:49 GMT"),
+            Some(1_716_285_889)
+        );
+    }
+}
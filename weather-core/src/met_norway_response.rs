@@ -0,0 +1,66 @@
+use alloc::{string::String, vec::Vec};
+
+use serde::Deserialize;
+
+use crate::model::DateTime;
+
+/// `locationforecast/2.0/compact` payload, a GeoJSON feature with the timeseries in its
+/// properties. Steps are hourly for the first days and 6-hourly after that.
+#[derive(Debug, Deserialize)]
+pub struct LocationForecastResponse {
+    pub properties: ForecastProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastProperties {
+    pub timeseries: Vec<TimeStep>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeStep {
+    /// Always UTC
+    pub time: DateTime,
+    pub data: TimeStepData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeStepData {
+    pub instant: InstantData,
+    pub next_1_hours: Option<PeriodData>,
+    pub next_6_hours: Option<PeriodData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstantData {
+    pub details: InstantDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstantDetails {
+    pub air_temperature: Option<f32>,
+    pub relative_humidity: Option<f32>,
+    /// m/s
+    pub wind_speed: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeriodData {
+    pub summary: PeriodSummary,
+    #[serde(default)]
+    pub details: PeriodDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeriodSummary {
+    /// e.g. `partlycloudy_day`, `heavyrainandthunder`
+    pub symbol_code: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PeriodDetails {
+    /// mm
+    pub precipitation_amount: Option<f32>,
+    /// Extremes over the period, only given for 6-hour periods
+    pub air_temperature_max: Option<f32>,
+    pub air_temperature_min: Option<f32>,
+}
@@ -0,0 +1,654 @@
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{fmt, str::Utf8Error, time::Duration};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// What a request failed with underneath, only `Debug` without `std::error::Error`
+#[cfg(feature = "std")]
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+#[cfg(not(feature = "std"))]
+pub type TransportError = Box<dyn fmt::Debug + Send + Sync>;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// No response could be read, e.g. DNS, TLS or a dropped connection
+    Transport(TransportError),
+    /// The server stopped answering before the response was complete
+    Timeout(TransportError),
+    /// The server answered with a status outside 2xx
+    HttpStatus(u16),
+    /// The body went on past the configured maximum size, in bytes
+    BodyTooLarge {
+        limit: usize,
+    },
+    InvalidUtf8(Utf8Error),
+    /// The body is not well-formed JSON, lines and columns start at 1
+    JsonSyntax {
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    /// `path` is where the field should be, e.g. `weatherForecast[3].forecastMaxtemp.value`
+    MissingField {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// The field at `path` has the wrong type or an unexpected value
+    InvalidField {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// The response parsed but has nothing to show, e.g. an empty list of stations
+    EmptyResponse,
+    UnknownStation {
+        name: String,
+        available: Vec<String>,
+    },
+    UnknownRainfallDistrict {
+        name: String,
+        available: Vec<String>,
+    },
+}
+
+/// `ApiError` without its details, for deciding what to do about an error
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApiErrorKind {
+    Transport,
+    Timeout,
+    /// 5xx, or 429 Too Many Requests
+    ServerError,
+    /// Any other status outside 2xx
+    ClientError,
+    BodyTooLarge,
+    InvalidUtf8,
+    JsonSyntax,
+    MissingField,
+    InvalidField,
+    EmptyResponse,
+    UnknownStation,
+    UnknownRainfallDistrict,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    English,
+    TraditionalChinese,
+    SimplifiedChinese,
+}
+
+/// HKO weather icons, the icon code is noted on each variant
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Weather {
+    /// 50
+    Sunny,
+    /// 51
+    SunnyPeriods,
+    /// 52
+    SunnyIntervals,
+    /// 53
+    SunnyPeriodsWithShowers,
+    /// 54
+    SunnyIntervalsWithShowers,
+    /// 60
+    Cloudly,
+    /// 61
+    Overcast,
+    /// 62
+    LightRain,
+    /// 63
+    Rain,
+    /// 64
+    HeavyRain,
+    /// 65
+    Thunderstorm,
+    /// 70 to 75, one code per moon phase
+    FineNight,
+    /// 76
+    MainlyCloudyNight,
+    /// 77
+    MainlyFineNight,
+    /// 80
+    Windy,
+    /// 81
+    Dry,
+    /// 82
+    Humid,
+    /// 83
+    Fog,
+    /// 84
+    Mist,
+    /// 85
+    Haze,
+    /// 90
+    Hot,
+    /// 91
+    Warm,
+    /// 92
+    Cool,
+    /// 93
+    Cold,
+    Unknow,
+}
+
+/// Probability of significant rain
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Psr {
+    High,
+    MediumHigh,
+    Medium,
+    MediumLow,
+    Low,
+    Unknow,
+}
+
+/// Tropical cyclone warning signals, strongest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TyphoonSignal {
+    Hurricane,
+    IncreasingGale,
+    GaleNorthEast,
+    GaleSouthEast,
+    GaleSouthWest,
+    GaleNorthWest,
+    Strong,
+    Standby,
+}
+
+/// Warnings listed in HKO's warning summary, in the order they are shown on the banner
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningSignal {
+    TropicalCyclone(TyphoonSignal),
+    RainstormBlack,
+    RainstormRed,
+    RainstormAmber,
+    Thunderstorm,
+    Landslip,
+    NorthernNewTerritoriesFlooding,
+    Tsunami,
+    StrongMonsoon,
+    VeryHot,
+    Cold,
+    Frost,
+    FireDangerRed,
+    FireDangerYellow,
+}
+
+/// Date and time as published, in the UTC offset it was given in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Minutes east of UTC, 480 for Hong Kong
+    pub utc_offset_minutes: i16,
+}
+
+/// Full text of a warning from HKO's `warningInfo`
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarningStatement {
+    /// Warning type, e.g. `WTCSGNL` or `WRAIN`
+    pub code: String,
+    pub signal: Option<WarningSignal>,
+    pub contents: Vec<String>,
+    pub update_time: DateTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecialWeatherTip {
+    pub desc: String,
+    pub update_time: DateTime,
+}
+
+/// Text forecast from HKO's `flw`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalForecastText {
+    pub general_situation: String,
+    /// Tropical cyclone information, empty when there is none
+    pub tc_info: String,
+    pub fire_danger_warning: String,
+    /// e.g. "Weather forecast for today"
+    pub forecast_period: String,
+    pub forecast_desc: String,
+    pub outlook: String,
+    pub update_time: DateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WeatherForecast {
+    pub date: u8,
+    pub week: String,
+    pub max_temp: i8,
+    pub min_temp: i8,
+    pub weather: Weather,
+    pub wind: String,
+    pub weather_text: String,
+    pub max_humidity: u8,
+    pub min_humidity: u8,
+    pub psr: Psr,
+}
+
+#[derive(Clone, Debug)]
+pub struct SeaTemperature {
+    pub place: String,
+    pub temp: f32,
+    pub record_time: DateTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct SoilTemperature {
+    pub place: String,
+    pub temp: f32,
+    /// Depth below ground in metres
+    pub depth: f32,
+    pub record_time: DateTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct NineDayForecast {
+    pub general_situation: String,
+    pub update_time: DateTime,
+    pub forecasts: Vec<WeatherForecast>,
+    pub sea_temp: Option<SeaTemperature>,
+    pub soil_temps: Vec<SoilTemperature>,
+}
+
+/// Coordinates for the providers that forecast anywhere
+#[derive(Clone)]
+pub struct Location {
+    pub latitude: f32,
+    pub longitude: f32,
+    /// Shown as the place of the current weather report
+    pub place: String,
+}
+
+impl Default for Location {
+    fn default() -> Self {
+        Self {
+            latitude: 22.3303,
+            longitude: 114.1622,
+            place: String::from("Sham Shui Po"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WeatherReport {
+    pub place: String,
+    pub temp: i8,
+    pub weather: Weather,
+    /// When the readings were taken
+    pub update_time: DateTime,
+    /// Maximum rainfall in the past hour in mm, `None` while the district is under maintenance
+    pub rainfall: Option<u16>,
+}
+
+impl WarningSignal {
+    pub fn label(&self, language: Language) -> &'static str {
+        // (English, Traditional Chinese, Simplified Chinese)
+        let labels = match self {
+            Self::TropicalCyclone(signal) => {
+                let code = match signal {
+                    TyphoonSignal::Hurricane => "T10",
+                    TyphoonSignal::IncreasingGale => "T9",
+                    TyphoonSignal::GaleNorthEast => "T8NE",
+                    TyphoonSignal::GaleSouthEast => "T8SE",
+                    TyphoonSignal::GaleSouthWest => "T8SW",
+                    TyphoonSignal::GaleNorthWest => "T8NW",
+                    TyphoonSignal::Strong => "T3",
+                    TyphoonSignal::Standby => "T1",
+                };
+                (code, code, code)
+            }
+            Self::RainstormBlack => ("BLACK RAIN", "黑雨", "黑雨"),
+            Self::RainstormRed => ("RED RAIN", "紅雨", "红雨"),
+            Self::RainstormAmber => ("AMBER RAIN", "黃雨", "黄雨"),
+            Self::Thunderstorm => ("THUNDER", "雷暴", "雷暴"),
+            Self::Landslip => ("LANDSLIP", "山泥", "山泥"),
+            Self::NorthernNewTerritoriesFlooding => ("NT FLOOD", "新界北水浸", "新界北水浸"),
+            Self::Tsunami => ("TSUNAMI", "海嘯", "海啸"),
+            Self::StrongMonsoon => ("MONSOON", "強季候風", "强季候风"),
+            Self::VeryHot => ("VERY HOT", "酷熱", "酷热"),
+            Self::Cold => ("COLD", "寒冷", "寒冷"),
+            Self::Frost => ("FROST", "霜凍", "霜冻"),
+            Self::FireDangerRed => ("RED FIRE", "紅色火災", "红色火灾"),
+            Self::FireDangerYellow => ("YELLOW FIRE", "黃色火災", "黄色火灾"),
+        };
+
+        language.pick(labels)
+    }
+}
+
+impl Language {
+    /// Choose from texts given as (English, Traditional Chinese, Simplified Chinese)
+    pub fn pick<'a>(self, texts: (&'a str, &'a str, &'a str)) -> &'a str {
+        match self {
+            Self::English => texts.0,
+            Self::TraditionalChinese => texts.1,
+            Self::SimplifiedChinese => texts.2,
+        }
+    }
+}
+
+impl SpecialWeatherTip {
+    pub fn is_newer_than(&self, update_time: &DateTime) -> bool {
+        self.update_time.unix_time() > update_time.unix_time()
+    }
+}
+
+/// Nearest whole number, halfway cases away from zero, as `f32::round` needs `std`
+pub fn round(value: f32) -> f32 {
+    let magnitude = if value < 0.0 { -value } else { value };
+    // Already whole, or too large for the cast below
+    if magnitude >= 8_388_608.0 || value.is_nan() {
+        return value;
+    }
+
+    let half = if value < 0.0 { -0.5 } else { 0.5 };
+    (value + half) as i32 as f32
+}
+
+/// Days since 1970-01-01, from Howard Hinnant's `days_from_civil`
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Year, month and day of a `days_from_civil` value
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+impl DateTime {
+    /// ISO 8601 with an offset, such as "2024-05-20T11:30:00+08:00" or "2024-05-20T03:30:00Z"
+    pub fn parse(text: &str) -> Option<Self> {
+        let (date, time) = text.split_once('T')?;
+        let zone_start = time.find(['Z', '+', '-'])?;
+        let (clock, zone) = time.split_at(zone_start);
+
+        let utc_offset_minutes = match zone {
+            "Z" => 0,
+            _ => {
+                let sign = if zone.starts_with('-') { -1 } else { 1 };
+                let digits = zone[1..].replace(':', "");
+                let hours: i16 = digits.get(0..2)?.parse().ok()?;
+                let minutes: i16 = digits.get(2..4)?.parse().ok()?;
+                sign * (hours * 60 + minutes)
+            }
+        };
+
+        let mut fields = clock.split(':');
+        let hour = fields.next()?.parse().ok()?;
+        let minute = fields.next()?.parse().ok()?;
+        // Fractions of a second are dropped
+        let second = match fields.next() {
+            Some(second) => second.split('.').next()?.parse().ok()?,
+            None => 0,
+        };
+
+        let date_time = Self {
+            year: date.get(0..4)?.parse().ok()?,
+            month: date.get(5..7)?.parse().ok()?,
+            day: date.get(8..10)?.parse().ok()?,
+            hour,
+            minute,
+            second,
+            utc_offset_minutes,
+        };
+
+        let is_valid = (1..=12).contains(&date_time.month)
+            && (1..=31).contains(&date_time.day)
+            && date_time.hour < 24
+            && date_time.minute < 60
+            && date_time.second <= 60;
+        is_valid.then_some(date_time)
+    }
+
+    pub fn from_unix_time(time: i64, utc_offset_minutes: i16) -> Self {
+        let local = time + i64::from(utc_offset_minutes) * 60;
+        let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+        let seconds = local.rem_euclid(SECONDS_PER_DAY);
+
+        Self {
+            year: year as i32,
+            month: month as u8,
+            day: day as u8,
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+            utc_offset_minutes,
+        }
+    }
+
+    /// Seconds since the Unix epoch
+    pub fn unix_time(&self) -> i64 {
+        let days = days_from_civil(
+            i64::from(self.year),
+            u32::from(self.month),
+            u32::from(self.day),
+        );
+        let seconds =
+            i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second);
+
+        days * SECONDS_PER_DAY + seconds - i64::from(self.utc_offset_minutes) * 60
+    }
+
+    /// `None` when `now`, seconds since the Unix epoch, is earlier, e.g. before SNTP has set
+    /// the clock
+    pub fn age(&self, now: i64) -> Option<Duration> {
+        let age = now.checked_sub(self.unix_time())?;
+        u64::try_from(age).ok().map(Duration::from_secs)
+    }
+
+    /// "11:30"
+    pub fn clock(&self) -> String {
+        format!("{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.utc_offset_minutes < 0 {
+            '-'
+        } else {
+            '+'
+        };
+        let offset = self.utc_offset_minutes.unsigned_abs();
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            sign,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Self::parse(&text).ok_or_else(|| de::Error::custom(format!("invalid date-time {:?}", text)))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(_) => write!(f, "request failed"),
+            Self::Timeout(_) => write!(f, "request timed out"),
+            Self::HttpStatus(status) => write!(f, "server responded with HTTP {}", status),
+            Self::BodyTooLarge { limit } => {
+                write!(f, "response body is larger than {} bytes", limit)
+            }
+            Self::InvalidUtf8(_) => write!(f, "response is not valid UTF-8"),
+            Self::JsonSyntax { line, column, .. } => {
+                write!(f, "invalid JSON at line {} column {}", line, column)
+            }
+            Self::MissingField { path, .. } => write!(f, "missing field {}", path),
+            Self::InvalidField { path, .. } => write!(f, "invalid field {}", path),
+            Self::EmptyResponse => write!(f, "response has no data"),
+            Self::UnknownStation { name, available } => write!(
+                f,
+                "unknown station {}, available: {}",
+                name,
+                available.join(", ")
+            ),
+            Self::UnknownRainfallDistrict { name, available } => write!(
+                f,
+                "unknown rainfall district {}, available: {}",
+                name,
+                available.join(", ")
+            ),
+        }
+    }
+}
+
+impl ApiError {
+    pub fn kind(&self) -> ApiErrorKind {
+        match self {
+            Self::Transport(_) => ApiErrorKind::Transport,
+            Self::Timeout(_) => ApiErrorKind::Timeout,
+            Self::HttpStatus(429 | 500..=599) => ApiErrorKind::ServerError,
+            Self::HttpStatus(_) => ApiErrorKind::ClientError,
+            Self::BodyTooLarge { .. } => ApiErrorKind::BodyTooLarge,
+            Self::InvalidUtf8(_) => ApiErrorKind::InvalidUtf8,
+            Self::JsonSyntax { .. } => ApiErrorKind::JsonSyntax,
+            Self::MissingField { .. } => ApiErrorKind::MissingField,
+            Self::InvalidField { .. } => ApiErrorKind::InvalidField,
+            Self::EmptyResponse => ApiErrorKind::EmptyResponse,
+            Self::UnknownStation { .. } => ApiErrorKind::UnknownStation,
+            Self::UnknownRainfallDistrict { .. } => ApiErrorKind::UnknownRainfallDistrict,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(source) | Self::Timeout(source) => Some(source.as_ref()),
+            Self::InvalidUtf8(source) => Some(source),
+            Self::JsonSyntax { source, .. }
+            | Self::MissingField { source, .. }
+            | Self::InvalidField { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Default for WeatherForecast {
+    fn default() -> Self {
+        Self {
+            min_temp: 0,
+            max_temp: 0,
+            date: 0,
+            week: String::from("---"),
+            weather: Weather::Unknow,
+            wind: String::new(),
+            weather_text: String::new(),
+            max_humidity: 0,
+            min_humidity: 0,
+            psr: Psr::Unknow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_half_away_from_zero() {
+        assert_eq!(round(26.5), 27.0);
+        assert_eq!(round(26.49), 26.0);
+        assert_eq!(round(-0.5), -1.0);
+        assert_eq!(round(-3.2), -3.0);
+        assert_eq!(round(1.0e10), 1.0e10);
+    }
+
+    #[test]
+    fn calendar_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+
+    #[test]
+    fn parse_hko_time() {
+        let time = DateTime::parse("2024-05-20T11:30:00+08:00").unwrap();
+
+        assert_eq!((time.year, time.month, time.day), (2024, 5, 20));
+        assert_eq!(time.clock(), "11:30");
+        assert_eq!(time.utc_offset_minutes, 480);
+        assert_eq!(time.unix_time(), 1_716_175_800);
+        assert_eq!(time.to_string(), "2024-05-20T11:30:00+08:00");
+    }
+
+    #[test]
+    fn parse_other_offsets() {
+        let utc = DateTime::parse("2024-05-20T03:30:00Z").unwrap();
+        assert_eq!(utc.unix_time(), 1_716_175_800);
+
+        let west = DateTime::parse("2024-05-19T23:30-04:00").unwrap();
+        assert_eq!(west.unix_time(), 1_716_175_800);
+        assert_eq!(west.to_string(), "2024-05-19T23:30:00-04:00");
+    }
+
+    #[test]
+    fn reject_malformed_time() {
+        assert_eq!(DateTime::parse(""), None);
+        assert_eq!(DateTime::parse("2024-05"), None);
+        // No offset, so the instant is unknown
+        assert_eq!(DateTime::parse("2024-05-20T11:30:00"), None);
+        assert_eq!(DateTime::parse("2024-13-20T11:30:00+08:00"), None);
+        assert_eq!(DateTime::parse("2024-05-20T25:30:00+08:00"), None);
+    }
+
+    #[test]
+    fn from_unix_time_in_hong_kong() {
+        let time = DateTime::from_unix_time(1_716_175_800, 480);
+
+        assert_eq!(time, DateTime::parse("2024-05-20T11:30:00+08:00").unwrap());
+        assert_eq!(
+            time.age(1_716_175_800 + 120),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(time.age(30), None);
+    }
+}
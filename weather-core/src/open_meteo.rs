@@ -0,0 +1,149 @@
+use alloc::{borrow::ToOwned, format, vec::Vec};
+
+use crate::model::*;
+use crate::open_meteo_response::{CurrentWeatherResponse, DailyVariables};
+
+impl Weather {
+    /// Icon for a WMO weather interpretation code, `is_day` picks the night icons for clear skies
+    pub fn from_wmo_code(code: u8, is_day: bool) -> Self {
+        match (code, is_day) {
+            (0, true) => Self::Sunny,
+            (0, false) => Self::FineNight,
+            (1, true) => Self::SunnyPeriods,
+            (1, false) => Self::MainlyFineNight,
+            (2, true) => Self::SunnyIntervals,
+            (2, false) => Self::MainlyCloudyNight,
+            (3, _) => Self::Overcast,
+            (45 | 48, _) => Self::Fog,
+            (51 | 53 | 55 | 56 | 57 | 61, _) => Self::LightRain,
+            (63 | 66, _) => Self::Rain,
+            (65 | 67 | 82, _) => Self::HeavyRain,
+            (80 | 81, true) => Self::SunnyIntervalsWithShowers,
+            (80 | 81, false) => Self::Rain,
+            (71 | 73 | 75 | 77 | 85 | 86, _) => Self::Cold,
+            (95 | 96 | 99, _) => Self::Thunderstorm,
+            _ => Self::Unknow,
+        }
+    }
+}
+
+impl Psr {
+    /// Bands of HKO's probability of significant rain applied to a precipitation probability
+    pub fn from_probability(percent: u8) -> Self {
+        match percent {
+            71.. => Self::High,
+            55..=70 => Self::MediumHigh,
+            45..=54 => Self::Medium,
+            30..=44 => Self::MediumLow,
+            _ => Self::Low,
+        }
+    }
+}
+
+fn wmo_description(code: u8) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51 => "Light drizzle",
+        53 => "Moderate drizzle",
+        55 => "Dense drizzle",
+        56 => "Light freezing drizzle",
+        57 => "Dense freezing drizzle",
+        61 => "Slight rain",
+        63 => "Moderate rain",
+        65 => "Heavy rain",
+        66 => "Light freezing rain",
+        67 => "Heavy freezing rain",
+        71 => "Slight snow fall",
+        73 => "Moderate snow fall",
+        75 => "Heavy snow fall",
+        77 => "Snow grains",
+        80 => "Slight rain showers",
+        81 => "Moderate rain showers",
+        82 => "Violent rain showers",
+        85 => "Slight snow showers",
+        86 => "Heavy snow showers",
+        95 => "Thunderstorm",
+        96 => "Thunderstorm with slight hail",
+        99 => "Thunderstorm with heavy hail",
+        _ => "",
+    }
+}
+
+/// "MON" for "2024-05-20", using Sakamoto's method
+fn short_weekday(date: &str) -> Option<&'static str> {
+    const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+    const MONTH_OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+
+    let year: i32 = date.get(0..4)?.parse().ok()?;
+    let month: usize = date.get(5..7)?.parse().ok()?;
+    let day: i32 = date.get(8..10)?.parse().ok()?;
+
+    let offset = MONTH_OFFSETS.get(month.checked_sub(1)?)?;
+    let year = if month < 3 { year - 1 } else { year };
+    let weekday = (year + year / 4 - year / 100 + year / 400 + offset + day).rem_euclid(7);
+
+    Some(WEEKDAYS[weekday as usize])
+}
+
+fn daily_forecast(daily: &DailyVariables, i: usize) -> WeatherForecast {
+    let date = &daily.time[i];
+    let value = |values: &Vec<Option<f32>>| values.get(i).copied().flatten();
+    let percent = |values: &Vec<Option<u8>>| values.get(i).copied().flatten();
+
+    let code = percent(&daily.weather_code);
+    let wind = value(&daily.wind_speed_10m_max);
+
+    WeatherForecast {
+        date: date.get(8..10).and_then(|d| d.parse().ok()).unwrap_or(0),
+        week: short_weekday(date).unwrap_or("---").to_owned(),
+        max_temp: value(&daily.temperature_2m_max).map_or(0, |t| round(t) as i8),
+        min_temp: value(&daily.temperature_2m_min).map_or(0, |t| round(t) as i8),
+        weather: code.map_or(Weather::Unknow, |code| Weather::from_wmo_code(code, true)),
+        wind: wind.map(|w| format!("{:.0} km/h", w)).unwrap_or_default(),
+        weather_text: code.map(wmo_description).unwrap_or_default().to_owned(),
+        max_humidity: percent(&daily.relative_humidity_2m_max).unwrap_or(0),
+        min_humidity: percent(&daily.relative_humidity_2m_min).unwrap_or(0),
+        psr: percent(&daily.precipitation_probability_max)
+            .map_or(Psr::Unknow, Psr::from_probability),
+    }
+}
+
+pub fn daily_forecasts(daily: &DailyVariables) -> Vec<WeatherForecast> {
+    (0..daily.time.len())
+        .map(|i| daily_forecast(daily, i))
+        .collect()
+}
+
+/// Readings at the forecast grid point, shown as `place`
+pub fn current_weather(response: CurrentWeatherResponse, place: &str) -> WeatherReport {
+    let current = response.current;
+
+    WeatherReport {
+        place: place.to_owned(),
+        temp: round(current.temperature_2m) as i8,
+        weather: Weather::from_wmo_code(current.weather_code, current.is_day != 0),
+        update_time: DateTime::from_unix_time(
+            current.time,
+            (response.utc_offset_seconds / 60) as i16,
+        ),
+        // Open-Meteo reports the last 15 minutes rather than HKO's hourly maximum
+        rainfall: Some(round(current.precipitation) as u16),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekday_across_years() {
+        assert_eq!(short_weekday("2000-01-01"), Some("SAT"));
+        assert_eq!(short_weekday("2024-02-29"), Some("THU"));
+        assert_eq!(short_weekday("2024-13-01"), None);
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::{format, vec::Vec};
+
+use serde::de::DeserializeOwned;
+
+use crate::http::HttpClient;
+use crate::model::*;
+use crate::open_meteo;
+use crate::open_meteo_response::{CurrentWeatherResponse, DailyForecastResponse};
+use crate::weather_provider::WeatherProvider;
+
+const OPEN_METEO_API_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+const DAILY_VARIABLES: &str = "weather_code,temperature_2m_max,temperature_2m_min,\
+relative_humidity_2m_max,relative_humidity_2m_min,precipitation_probability_max,wind_speed_10m_max";
+const CURRENT_VARIABLES: &str = "temperature_2m,weather_code,is_day,precipitation";
+
+/// Same number of days as the HKO 9-day forecast
+const FORECAST_DAYS: u8 = 9;
+
+pub struct OpenMeteoApi<C: HttpClient> {
+    http_client: C,
+    location: Location,
+}
+
+impl<C> OpenMeteoApi<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, location: Location) -> Self {
+        Self {
+            http_client: client,
+            location,
+        }
+    }
+
+    fn get_request_typed<T: DeserializeOwned>(&mut self, variables: &str) -> Result<T, ApiError> {
+        let url = format!(
+            "{}?latitude={}&longitude={}&{}&timezone=auto&forecast_days={}",
+            OPEN_METEO_API_URL,
+            self.location.latitude,
+            self.location.longitude,
+            variables,
+            FORECAST_DAYS
+        );
+        let response = self.http_client.get_json(&url, &[])?.error_for_status()?;
+        response.body.ok_or(ApiError::EmptyResponse)
+    }
+}
+
+impl<C> WeatherProvider for OpenMeteoApi<C>
+where
+    C: HttpClient,
+{
+    fn fetch_forecast(&mut self) -> Result<Vec<WeatherForecast>, ApiError> {
+        let response: DailyForecastResponse =
+            self.get_request_typed(&format!("daily={}", DAILY_VARIABLES))?;
+
+        Ok(open_meteo::daily_forecasts(&response.daily))
+    }
+
+    fn fetch_current_weather(&mut self) -> Result<WeatherReport, ApiError> {
+        let response: CurrentWeatherResponse = self.get_request_typed(&format!(
+            "current={}&timeformat=unixtime",
+            CURRENT_VARIABLES
+        ))?;
+
+        Ok(open_meteo::current_weather(response, &self.location.place))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::CannedHttpClient;
+
+    const DAILY_FORECAST: &str = r#"{
+        "latitude": 22.3,
+        "longitude": 114.2,
+        "timezone": "Asia/Hong_Kong",
+        "daily_units": {"time": "iso8601", "weather_code": "wmo code"},
+        "daily": {
+            "time": ["2024-05-20", "2024-05-21"],
+            "weather_code": [2, 95],
+            "temperature_2m_max": [29.6, 27.2],
+            "temperature_2m_min": [25.4, 24.5],
+            "relative_humidity_2m_max": [95, 98],
+            "relative_humidity_2m_min": [70, 80],
+            "precipitation_probability_max": [35, null],
+            "wind_speed_10m_max": [18.7, 22.1]
+        }
+    }"#;
+
+    const CURRENT_WEATHER: &str = r#"{
+        "latitude": 22.3,
+        "longitude": 114.2,
+        "utc_offset_seconds": 28800,
+        "current": {
+            "time": 1716214500,
+            "interval": 900,
+            "temperature_2m": 26.6,
+            "weather_code": 0,
+            "is_day": 0,
+            "precipitation": 0.0
+        }
+    }"#;
+
+    #[test]
+    fn forecast_from_daily_variables() {
+        let mut api = OpenMeteoApi::new(CannedHttpClient::new(DAILY_FORECAST), Location::default());
+
+        let forecasts = api.fetch_forecast().unwrap();
+        assert_eq!(forecasts.len(), 2);
+
+        let today = &forecasts[0];
+        assert_eq!(today.date, 20);
+        assert_eq!(today.week, "MON");
+        assert_eq!((today.min_temp, today.max_temp), (25, 30));
+        assert_eq!(today.weather, Weather::SunnyIntervals);
+        assert_eq!(today.weather_text, "Partly cloudy");
+        assert_eq!(today.wind, "19 km/h");
+        assert_eq!(today.psr, Psr::MediumLow);
+
+        let tomorrow = &forecasts[1];
+        assert_eq!(tomorrow.week, "TUE");
+        assert_eq!(tomorrow.weather, Weather::Thunderstorm);
+        assert_eq!(tomorrow.psr, Psr::Unknow);
+
+        let url = &api.http_client.requests[0].0;
+        assert!(url.contains("latitude=22.3303&longitude=114.1622"));
+        assert!(url.contains("daily=weather_code,"));
+    }
+
+    #[test]
+    fn current_weather_at_night() {
+        let mut api =
+            OpenMeteoApi::new(CannedHttpClient::new(CURRENT_WEATHER), Location::default());
+
+        let report = api.fetch_current_weather().unwrap();
+        assert_eq!(report.place, "Sham Shui Po");
+        assert_eq!(report.temp, 27);
+        assert_eq!(report.weather, Weather::FineNight);
+        assert_eq!(report.update_time.to_string(), "2024-05-20T22:15:00+08:00");
+        assert_eq!(report.rainfall, Some(0));
+    }
+}
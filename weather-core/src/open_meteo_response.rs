@@ -0,0 +1,42 @@
+use alloc::{string::String, vec::Vec};
+
+use serde::Deserialize;
+
+/// `/v1/forecast` payload for the `daily` variables, one array entry per day.
+/// Far days can be `null` for variables the models don't reach.
+#[derive(Debug, Deserialize)]
+pub struct DailyForecastResponse {
+    pub daily: DailyVariables,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyVariables {
+    /// `YYYY-MM-DD` in the location's timezone
+    pub time: Vec<String>,
+    pub weather_code: Vec<Option<u8>>,
+    pub temperature_2m_max: Vec<Option<f32>>,
+    pub temperature_2m_min: Vec<Option<f32>>,
+    pub relative_humidity_2m_max: Vec<Option<u8>>,
+    pub relative_humidity_2m_min: Vec<Option<u8>>,
+    pub precipitation_probability_max: Vec<Option<u8>>,
+    pub wind_speed_10m_max: Vec<Option<f32>>,
+}
+
+/// `/v1/forecast` payload for the `current` variables
+#[derive(Debug, Deserialize)]
+pub struct CurrentWeatherResponse {
+    /// Of the timezone picked by `timezone=auto`
+    pub utc_offset_seconds: i32,
+    pub current: CurrentVariables,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrentVariables {
+    /// Seconds since the Unix epoch, requested with `timeformat=unixtime`
+    pub time: i64,
+    pub temperature_2m: f32,
+    pub weather_code: u8,
+    pub is_day: u8,
+    /// Millimetres in the preceding 15 minutes
+    pub precipitation: f32,
+}
@@ -0,0 +1,229 @@
+use alloc::{vec, vec::Vec};
+use core::{convert::Infallible, fmt};
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use epd_waveshare::color::TriColor;
+use epd_waveshare::graphics::DisplayRotation;
+
+#[cfg(test)]
+use crate::frame::TriColorFrame;
+
+/// Whether the chromatic color can be shown, panels without it print it in black
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorCapability {
+    BlackWhite,
+    TriColor,
+}
+
+/// An e-paper panel with the frame buffer that is drawn into, sized as drawn with the panel's
+/// rotation applied
+pub trait Panel: DrawTarget<Color = TriColor, Error = Infallible> + OriginDimensions {
+    /// What sending to the panel fails with
+    type UpdateError: fmt::Debug;
+
+    fn color_capability(&self) -> ColorCapability;
+
+    /// Clear the frame buffer to white, before the first screen is drawn
+    fn init(&mut self);
+
+    /// Send the frame buffer and refresh the whole panel, partial updates refresh against it
+    fn full_update(&mut self) -> Result<(), Self::UpdateError>;
+
+    /// Send and refresh only `areas` of the frame buffer, quicker and without flashing. Panels
+    /// that can't fall back to a full update.
+    fn partial_update(&mut self, areas: &[Rectangle]) -> Result<(), Self::UpdateError>;
+
+    fn sleep(&mut self) -> Result<(), Self::UpdateError>;
+
+    fn wake_up(&mut self) -> Result<(), Self::UpdateError>;
+}
+
+/// `area` as drawn, in the coordinates of a panel `width` by `height` before rotation
+pub fn native_area(
+    area: Rectangle,
+    rotation: DisplayRotation,
+    width: u32,
+    height: u32,
+) -> Rectangle {
+    let (x, y) = (area.top_left.x, area.top_left.y);
+    let (w, h) = (area.size.width as i32, area.size.height as i32);
+    let (width, height) = (width as i32, height as i32);
+
+    match rotation {
+        DisplayRotation::Rotate0 => area,
+        DisplayRotation::Rotate90 => Rectangle::new(
+            Point::new(width - y - h, x),
+            Size::new(area.size.height, area.size.width),
+        ),
+        DisplayRotation::Rotate180 => {
+            Rectangle::new(Point::new(width - x - w, height - y - h), area.size)
+        }
+        DisplayRotation::Rotate270 => Rectangle::new(
+            Point::new(y, height - x - w),
+            Size::new(area.size.height, area.size.width),
+        ),
+    }
+}
+
+/// Pixels of `area` out of a plane `width` pixels wide, packed into rows of their own
+pub fn window(plane: &[u8], width: u32, area: Rectangle) -> Vec<u8> {
+    let row_bytes = (width as usize + 7) / 8;
+    let window_row_bytes = (area.size.width as usize + 7) / 8;
+    let mut window = vec![0xff; window_row_bytes * area.size.height as usize];
+
+    for row in 0..area.size.height as usize {
+        let y = area.top_left.y as usize + row;
+
+        for column in 0..area.size.width as usize {
+            let x = area.top_left.x as usize + column;
+            let is_set = plane[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0;
+
+            if !is_set {
+                window[row * window_row_bytes + column / 8] &= !(0x80 >> (column % 8));
+            }
+        }
+    }
+
+    window
+}
+
+/// A call made on `MockPanel`
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PanelCall {
+    Init,
+    FullUpdate,
+    PartialUpdate(Vec<Rectangle>),
+    Sleep,
+    WakeUp,
+}
+
+/// Draws into a frame on the host and records what would have been sent to the panel
+#[cfg(test)]
+pub struct MockPanel {
+    pub frame: TriColorFrame,
+    pub calls: Vec<PanelCall>,
+    color_capability: ColorCapability,
+}
+
+#[cfg(test)]
+impl MockPanel {
+    pub fn new(size: Size, color_capability: ColorCapability) -> Self {
+        Self {
+            frame: TriColorFrame::new(size),
+            calls: Vec::new(),
+            color_capability,
+        }
+    }
+}
+
+#[cfg(test)]
+impl OriginDimensions for MockPanel {
+    fn size(&self) -> Size {
+        self.frame.size()
+    }
+}
+
+#[cfg(test)]
+impl DrawTarget for MockPanel {
+    type Color = TriColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.frame.draw_iter(pixels)
+    }
+}
+
+#[cfg(test)]
+impl Panel for MockPanel {
+    type UpdateError = Infallible;
+
+    fn color_capability(&self) -> ColorCapability {
+        self.color_capability
+    }
+
+    fn init(&mut self) {
+        _ = self.frame.clear(TriColor::White);
+        self.calls.push(PanelCall::Init);
+    }
+
+    fn full_update(&mut self) -> Result<(), Infallible> {
+        self.calls.push(PanelCall::FullUpdate);
+        Ok(())
+    }
+
+    fn partial_update(&mut self, areas: &[Rectangle]) -> Result<(), Infallible> {
+        self.calls.push(PanelCall::PartialUpdate(areas.to_vec()));
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<(), Infallible> {
+        self.calls.push(PanelCall::Sleep);
+        Ok(())
+    }
+
+    fn wake_up(&mut self) -> Result<(), Infallible> {
+        self.calls.push(PanelCall::WakeUp);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::Screen;
+
+    #[test]
+    fn readings_on_the_2in9b_panel() {
+        let screen = Screen::new(Size::new(296, 128));
+        let native = |area| native_area(area, DisplayRotation::Rotate270, 128, 296);
+
+        // Where the readings were sent before there was a panel trait
+        assert_eq!(
+            native(screen.temperature_reading()),
+            Rectangle::new(Point::new(22, 0), Size::new(24, 40))
+        );
+        assert_eq!(
+            native(screen.humidity_reading()),
+            Rectangle::new(Point::new(70, 0), Size::new(24, 40))
+        );
+    }
+
+    #[test]
+    fn native_area_of_every_rotation() {
+        let area = Rectangle::new(Point::new(1, 2), Size::new(3, 4));
+
+        let native = |rotation| native_area(area, rotation, 10, 20);
+
+        assert_eq!(native(DisplayRotation::Rotate0), area);
+        assert_eq!(
+            native(DisplayRotation::Rotate90),
+            Rectangle::new(Point::new(4, 1), Size::new(4, 3))
+        );
+        assert_eq!(
+            native(DisplayRotation::Rotate180),
+            Rectangle::new(Point::new(6, 14), Size::new(3, 4))
+        );
+        assert_eq!(
+            native(DisplayRotation::Rotate270),
+            Rectangle::new(Point::new(2, 16), Size::new(4, 3))
+        );
+    }
+
+    #[test]
+    fn window_is_packed_into_its_own_rows() {
+        // 16 pixels wide, the second row has pixels 3 to 12 cleared
+        let plane = [0xff, 0xff, 0b1110_0000, 0b0000_0111];
+
+        let window = window(
+            &plane,
+            16,
+            Rectangle::new(Point::new(2, 0), Size::new(12, 2)),
+        );
+
+        assert_eq!(window, [0xff, 0xff, 0b1000_0000, 0b0001_1111]);
+    }
+}
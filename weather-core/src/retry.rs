@@ -0,0 +1,243 @@
+use alloc::{vec, vec::Vec};
+
+use crate::model::{ApiError, ApiErrorKind};
+
+/// Waits between attempts
+pub trait RetryDelay {
+    fn delay_ms(&mut self, ms: u32);
+}
+
+/// Source of the jitter added to every delay
+pub trait RandomSource {
+    fn next_u32(&mut self) -> u32;
+}
+
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Including the first one
+    pub max_attempts: u32,
+    /// Delay after the first failure, doubled after every further one
+    pub base_delay_ms: u32,
+    pub max_delay_ms: u32,
+    /// Up to this share of each delay is taken off at random, so devices rebooted together by a
+    /// power cut don't all retry at the same moment
+    pub jitter_percent: u8,
+    pub retry_on: Vec<ApiErrorKind>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 2_000,
+            max_delay_ms: 60_000,
+            jitter_percent: 50,
+            // A truncated body shows up as a JSON syntax error
+            retry_on: vec![
+                ApiErrorKind::Transport,
+                ApiErrorKind::Timeout,
+                ApiErrorKind::ServerError,
+                ApiErrorKind::JsonSyntax,
+            ],
+        }
+    }
+}
+
+/// Backs off exponentially between attempts, waiting on whatever `D` the platform provides
+pub struct Retry<D: RetryDelay, R: RandomSource> {
+    policy: RetryPolicy,
+    delay: D,
+    random: R,
+}
+
+impl<D, R> Retry<D, R>
+where
+    D: RetryDelay,
+    R: RandomSource,
+{
+    pub fn new(policy: RetryPolicy, delay: D, random: R) -> Self {
+        Self {
+            policy,
+            delay,
+            random,
+        }
+    }
+
+    /// Delay before attempt `attempt + 1`, after `attempt` attempts failed
+    fn delay_after(&mut self, attempt: u32) -> u32 {
+        let exponential = u64::from(self.policy.base_delay_ms)
+            .saturating_mul(1 << (attempt - 1).min(31))
+            .min(u64::from(self.policy.max_delay_ms));
+        let spread = exponential * u64::from(self.policy.jitter_percent.min(100)) / 100;
+        let jitter = u64::from(self.random.next_u32()) % (spread + 1);
+
+        (exponential - jitter) as u32
+    }
+
+    /// Call `operation` until it succeeds, fails with an error the policy doesn't retry, or
+    /// runs out of attempts. The last error is returned.
+    pub fn call<T>(
+        &mut self,
+        mut operation: impl FnMut() -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let mut attempt = 1;
+
+        loop {
+            let err = match operation() {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            if attempt >= self.policy.max_attempts || !self.policy.retry_on.contains(&err.kind()) {
+                return Err(err);
+            }
+
+            let delay_ms = self.delay_after(attempt);
+            log::warn!(
+                "Attempt {} of {} failed: {}, retrying in {} ms",
+                attempt,
+                self.policy.max_attempts,
+                err,
+                delay_ms
+            );
+            self.delay.delay_ms(delay_ms);
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{CannedHttpClient, HttpResponse};
+    use crate::weather_api::{WeatherApi, WeatherApiConfig};
+
+    const FND_FIXTURE: &str = include_str!("../tests/fixtures/hko/fnd.json");
+
+    /// Fails with the given errors in turn, then succeeds
+    struct Flaky {
+        failures: Vec<ApiError>,
+    }
+
+    impl Flaky {
+        fn call(&mut self) -> Result<(), ApiError> {
+            if !self.failures.is_empty() {
+                return Err(self.failures.remove(0));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordedDelays(Vec<u32>);
+
+    impl RetryDelay for &mut RecordedDelays {
+        fn delay_ms(&mut self, ms: u32) {
+            self.0.push(ms);
+        }
+    }
+
+    struct FixedRandom(u32);
+
+    impl RandomSource for FixedRandom {
+        fn next_u32(&mut self) -> u32 {
+            self.0
+        }
+    }
+
+    fn timeout() -> ApiError {
+        ApiError::Timeout("timed out".into())
+    }
+
+    fn policy(jitter_percent: u8) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay_ms: 100,
+            max_delay_ms: 300,
+            jitter_percent,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn succeeds_after_failures() {
+        let mut flaky = Flaky {
+            failures: vec![timeout(), timeout()],
+        };
+        let mut delays = RecordedDelays::default();
+        let mut retry = Retry::new(policy(0), &mut delays, FixedRandom(0));
+
+        assert!(retry.call(|| flaky.call()).is_ok());
+        assert_eq!(delays.0, [100, 200]);
+    }
+
+    #[test]
+    fn gives_up_with_last_error() {
+        let mut flaky = Flaky {
+            failures: (0..10).map(|_| timeout()).collect(),
+        };
+        let mut delays = RecordedDelays::default();
+        let mut retry = Retry::new(policy(0), &mut delays, FixedRandom(0));
+
+        let result = retry.call(|| flaky.call());
+
+        assert!(matches!(result, Err(ApiError::Timeout(_))));
+        // Capped by max_delay_ms
+        assert_eq!(delays.0, [100, 200, 300]);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let mut flaky = Flaky {
+            failures: vec![ApiError::HttpStatus(404), timeout()],
+        };
+        let mut delays = RecordedDelays::default();
+        let mut retry = Retry::new(policy(0), &mut delays, FixedRandom(0));
+
+        let mut attempts = 0;
+        let result = retry.call(|| {
+            attempts += 1;
+            flaky.call()
+        });
+
+        assert!(matches!(result, Err(ApiError::HttpStatus(404))));
+        assert_eq!(attempts, 1);
+        assert!(delays.0.is_empty());
+    }
+
+    #[test]
+    fn jitter_takes_off_part_of_the_delay() {
+        let mut flaky = Flaky {
+            failures: vec![timeout(), timeout(), timeout()],
+        };
+        let mut delays = RecordedDelays::default();
+        let mut retry = Retry::new(policy(50), &mut delays, FixedRandom(30));
+
+        assert!(retry.call(|| flaky.call()).is_ok());
+        // 30 % 51, 30 % 101 and 30 % 151 taken off 100, 200 and 300
+        assert_eq!(delays.0, [70, 170, 270]);
+    }
+
+    /// A timeout, then a 503, then the forecast
+    #[test]
+    fn api_requests_are_retried() {
+        let mut client = CannedHttpClient::new(FND_FIXTURE);
+        client.queued = vec![
+            Err(timeout()),
+            Ok(HttpResponse {
+                status: 503,
+                headers: Vec::new(),
+                body: String::new(),
+            }),
+        ];
+        let mut api = WeatherApi::new(client, WeatherApiConfig::default());
+        let mut delays = RecordedDelays::default();
+        let mut retry = Retry::new(policy(0), &mut delays, FixedRandom(0));
+
+        let forecast = retry.call(|| api.fetch_nine_day_forecast()).unwrap();
+
+        assert_eq!(forecast.forecasts.len(), 9);
+        assert_eq!(delays.0, [100, 200]);
+    }
+}
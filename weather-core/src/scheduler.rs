@@ -0,0 +1,118 @@
+/// HKO publishes in Hong Kong time, UTC+8 all year
+const HKT_OFFSET_SECS: i64 = 8 * 60 * 60;
+
+/// Earlier times come from a clock SNTP hasn't set yet, 2023-11-14
+const CLOCK_SET_AFTER: i64 = 1_700_000_000;
+
+/// Wait before connecting again after the first failure, doubled after every further one
+const RECONNECT_AFTER_SECS: i64 = 60;
+
+/// Seconds since the Unix epoch, counting from boot until SNTP has synchronised
+#[cfg(feature = "std")]
+pub fn unix_time() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64)
+}
+
+pub fn is_clock_set(time: i64) -> bool {
+    time >= CLOCK_SET_AFTER
+}
+
+/// When to fetch the weather again: every `interval` counted from midnight Hong Kong time,
+/// delayed by `offset` so HKO has published by then
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RefreshSchedule {
+    interval_secs: i64,
+    offset_secs: i64,
+}
+
+impl Default for RefreshSchedule {
+    /// The current weather report is published every hour and the 9-day forecast at about
+    /// 11:30 and 16:30, so an hourly refresh at ten past picks both up
+    fn default() -> Self {
+        Self::new(60, 10)
+    }
+}
+
+impl RefreshSchedule {
+    pub fn new(interval_minutes: u16, offset_minutes: u16) -> Self {
+        let interval_secs = i64::from(interval_minutes.max(1)) * 60;
+
+        Self {
+            interval_secs,
+            offset_secs: (i64::from(offset_minutes) * 60) % interval_secs,
+        }
+    }
+
+    /// First refresh strictly after `now`, both in seconds since the Unix epoch.
+    ///
+    /// Before SNTP has set the clock this still spaces refreshes by the interval, and the jump
+    /// to the real time makes the next refresh due at once, after which they line up.
+    pub fn next_after(&self, now: i64) -> i64 {
+        let since_midnight = now + HKT_OFFSET_SECS - self.offset_secs;
+        let next = (since_midnight.div_euclid(self.interval_secs) + 1) * self.interval_secs;
+
+        next - HKT_OFFSET_SECS + self.offset_secs
+    }
+
+    /// When to try connecting again after `failures` attempts in a row failed, backing off
+    /// from a minute but no later than the next refresh
+    pub fn next_reconnect(&self, now: i64, failures: u32) -> i64 {
+        let backoff = RECONNECT_AFTER_SECS << failures.saturating_sub(1).min(16);
+
+        (now + backoff).min(self.next_after(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-05-20 00:00 HKT
+    const MIDNIGHT: i64 = 1_716_163_200 - HKT_OFFSET_SECS;
+
+    fn at(hour: i64, minute: i64) -> i64 {
+        MIDNIGHT + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn hourly_after_publishing() {
+        let schedule = RefreshSchedule::new(60, 10);
+
+        assert_eq!(schedule.next_after(at(10, 3)), at(10, 10));
+        assert_eq!(schedule.next_after(at(10, 10)), at(11, 10));
+        assert_eq!(schedule.next_after(at(23, 30)), at(24, 10));
+    }
+
+    #[test]
+    fn aligned_to_hong_kong_midnight() {
+        let schedule = RefreshSchedule::new(180, 40);
+
+        // 00:40, 03:40, 06:40, 09:40, 12:40 HKT
+        assert_eq!(schedule.next_after(at(10, 0)), at(12, 40));
+        assert_eq!(schedule.next_after(at(0, 0)), at(0, 40));
+    }
+
+    #[test]
+    fn offset_wraps_within_interval() {
+        let schedule = RefreshSchedule::new(30, 45);
+
+        assert_eq!(schedule.next_after(at(10, 0)), at(10, 15));
+        assert_eq!(schedule.next_after(at(10, 20)), at(10, 45));
+    }
+
+    #[test]
+    fn reconnect_backs_off_until_the_next_refresh() {
+        let schedule = RefreshSchedule::new(60, 10);
+        let now = at(10, 20);
+
+        assert_eq!(schedule.next_reconnect(now, 1), at(10, 21));
+        assert_eq!(schedule.next_reconnect(now, 2), at(10, 22));
+        assert_eq!(schedule.next_reconnect(now, 5), at(10, 36));
+        assert_eq!(schedule.next_reconnect(now, 7), at(11, 10));
+        assert_eq!(schedule.next_reconnect(now, 100), at(11, 10));
+    }
+}
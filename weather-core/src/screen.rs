@@ -0,0 +1,872 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::time::Duration;
+
+use embedded_graphics::{
+    image::Image,
+    mono_font::{MonoTextStyle, MonoTextStyleBuilder},
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle, StyledDrawable},
+    text::{Alignment, Text},
+};
+use embedded_icon::{
+    iconoir::size24px::{TemperatureHigh, WateringSoil},
+    iconoir::size32px::{
+        self, Cloud, CloudSunny, Droplet, FireFlame, Fog, HalfMoon, HeavyRain, Rain, SnowFlake,
+        SunLight, Thunderstorm, WarningCircle, Wind,
+    },
+    NewIcon,
+};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts,
+    types::{FontColor, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::layout::{
+    align, cell_scale, pad, sidebar_scale, text_width, Align, DashboardLayout, Padding,
+    MIN_CELL_SIZE,
+};
+use crate::model::{
+    DateTime, Language, LocalForecastText, SpecialWeatherTip, WarningSignal, Weather,
+    WeatherForecast, WeatherReport,
+};
+use crate::text_layout::{char_columns, text_columns, truncate_to_columns, wrap_text_truncated};
+
+/// 12px font for Chinese text, GB2312 covers simplified characters and the traditional ones
+/// they share. The others are drawn as `draw_missing_glyph`.
+const CJK_FONT: FontRenderer = FontRenderer::new::<fonts::u8g2_font_wqy12_t_gb2312>();
+
+/// Size of an indoor reading on the sidebar at scale 1, each is refreshed on its own by a
+/// partial update
+pub const READING_SIZE: Size = Size::new(40, 24);
+
+/// Top-left corner of the indoor temperature reading, from the top-left of the sidebar at
+/// scale 1
+const TEMPERATURE_READING: Point = Point::new(31, 22);
+
+/// Top-left corner of the indoor humidity reading, from the top-left of the sidebar at scale 1
+const HUMIDITY_READING: Point = Point::new(31, 70);
+
+/// Draws every pixel as a `scale` wide square from `origin`, so the layouts made for the
+/// smallest panel fill the larger ones with the same fonts and icons
+struct Scaled<'a, D> {
+    target: &'a mut D,
+    origin: Point,
+    scale: u32,
+}
+
+impl<'a, D: DrawTarget> Scaled<'a, D> {
+    fn new(target: &'a mut D, origin: Point, scale: u32) -> Self {
+        Scaled {
+            target,
+            origin,
+            scale: scale.max(1),
+        }
+    }
+}
+
+impl<D: DrawTarget> Dimensions for Scaled<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        let area = self.target.bounding_box();
+
+        Rectangle::new(
+            (area.top_left - self.origin) / self.scale as i32,
+            area.size / self.scale,
+        )
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Scaled<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = Size::new_equal(self.scale);
+
+        for Pixel(point, color) in pixels {
+            let top_left = self.origin + point * self.scale as i32;
+            self.target
+                .fill_solid(&Rectangle::new(top_left, size), color)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An empty box over the `width` a character would have taken, so a word the font lacks
+/// shows as missing instead of closing up the text around it
+fn draw_missing_glyph<D: DrawTarget<Color = TriColor>>(
+    display: &mut D,
+    position: Point,
+    width: u32,
+    color: TriColor,
+) {
+    _ = Rectangle::new(
+        position + Point::new(1, -9),
+        Size::new(width.saturating_sub(2), 10),
+    )
+    .draw_styled(&PrimitiveStyle::with_stroke(color, 1), display);
+}
+
+/// Draw `text` with the mono font of `style`, or with `CJK_FONT` when it is not plain ASCII.
+/// Either way every character takes the `text_columns` of the mono font, so the text lines
+/// up with what was measured. Returns where the next character would go.
+fn draw_text<D: DrawTarget<Color = TriColor>>(
+    display: &mut D,
+    text: &str,
+    position: Point,
+    style: MonoTextStyle<TriColor>,
+) -> Point {
+    if text.is_ascii() {
+        return Text::new(text, position, style)
+            .draw(display)
+            .unwrap_or(position);
+    }
+
+    let column_width = style.font.character_size.width + style.font.character_spacing;
+    let mut position = position;
+
+    for c in text.chars() {
+        let width = char_columns(c) as u32 * column_width;
+
+        if let Some(color) = style.text_color {
+            let rendered = CJK_FONT.render(
+                c,
+                position,
+                VerticalPosition::Baseline,
+                FontColor::Transparent(color),
+                display,
+            );
+            if rendered.is_err() {
+                draw_missing_glyph(display, position, width, color);
+            }
+        }
+        position.x += width as i32;
+    }
+
+    position
+}
+
+/// Where the weather on screen came from and when, `None` when the clock wasn't set yet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Freshness {
+    /// Fetched at this time, marked as old once `stale_after` has passed
+    Fetched(Option<DateTime>),
+    /// Loaded from storage at boot, saved at this time and always marked as old
+    Stored(Option<DateTime>),
+}
+
+/// "45m", "3h" or "2d"
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+
+    match minutes {
+        0..=59 => format!("{}m", minutes),
+        60..=2879 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    }
+}
+
+/// An indoor reading such as "27.5C" in the sidebar colours, drawn from the origin of
+/// `display` over `READING_SIZE` times `scale`, the `Screen::sidebar_scale`
+pub fn draw_reading<D: DrawTarget<Color = TriColor>>(display: &mut D, text: &str, scale: u32) {
+    let display = &mut Scaled::new(display, Point::zero(), scale);
+    let text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_7X13_BOLD)
+        .text_color(TriColor::White)
+        .build();
+
+    _ = display.fill_solid(
+        &Rectangle::new(Point::zero(), READING_SIZE),
+        TriColor::Black,
+    );
+    _ = Text::new(&fit_reading(text), Point::new(0, 16), text_style).draw(display);
+}
+
+/// `text` without its decimals when it is wider than `READING_SIZE`, "100.0%" as "100%"
+fn fit_reading(text: &str) -> String {
+    let columns = (READING_SIZE.width / 7) as usize;
+    let fraction = text.find('.').map(|dot| {
+        let digits = text[dot + 1..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .count();
+        dot..dot + 1 + digits
+    });
+
+    match fraction {
+        Some(fraction) if text.len() > columns => {
+            let mut text = text.to_owned();
+            text.replace_range(fraction, "");
+            text
+        }
+        _ => text.to_owned(),
+    }
+}
+
+fn draw_icon<D: DrawTarget<Color = TriColor>>(display: &mut D, weather: Weather, position: Point) {
+    let color = TriColor::Chromatic;
+
+    match weather {
+        Weather::Sunny => {
+            let _ = Image::new(&SunLight::new(color), position).draw(display);
+        }
+        Weather::SunnyPeriods | Weather::SunnyIntervals => {
+            let _ = Image::new(&CloudSunny::new(color), position).draw(display);
+        }
+        Weather::Cloudly | Weather::Overcast | Weather::MainlyCloudyNight => {
+            let _ = Image::new(&Cloud::new(color), position).draw(display);
+        }
+        Weather::SunnyPeriodsWithShowers
+        | Weather::SunnyIntervalsWithShowers
+        | Weather::LightRain
+        | Weather::Rain => {
+            let _ = Image::new(&Rain::new(color), position).draw(display);
+        }
+        Weather::HeavyRain => {
+            let _ = Image::new(&HeavyRain::new(color), position).draw(display);
+        }
+        Weather::Thunderstorm => {
+            let _ = Image::new(&Thunderstorm::new(color), position).draw(display);
+        }
+        Weather::FineNight | Weather::MainlyFineNight => {
+            let _ = Image::new(&HalfMoon::new(color), position).draw(display);
+        }
+        Weather::Windy => {
+            let _ = Image::new(&Wind::new(color), position).draw(display);
+        }
+        Weather::Dry => {
+            let _ = Image::new(&FireFlame::new(color), position).draw(display);
+        }
+        Weather::Humid => {
+            let _ = Image::new(&Droplet::new(color), position).draw(display);
+        }
+        Weather::Fog | Weather::Mist | Weather::Haze => {
+            let _ = Image::new(&Fog::new(color), position).draw(display);
+        }
+        Weather::Hot | Weather::Warm => {
+            let _ = Image::new(&size32px::TemperatureHigh::new(color), position).draw(display);
+        }
+        Weather::Cool => {
+            let _ = Image::new(&size32px::TemperatureLow::new(color), position).draw(display);
+        }
+        Weather::Cold => {
+            let _ = Image::new(&SnowFlake::new(color), position).draw(display);
+        }
+        Weather::Unknow => {
+            let _ = Image::new(&WarningCircle::new(color), position).draw(display);
+        }
+    };
+}
+
+/// A day's forecast in the middle of `cell`, the icon beside the date and the day of the
+/// week over the temperatures, scaled up as far as the cell allows
+fn draw_forecast_cell<D: DrawTarget<Color = TriColor>>(
+    display: &mut D,
+    cell: Rectangle,
+    forecast: &WeatherForecast,
+) {
+    let text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_7X13_BOLD)
+        .text_color(TriColor::Black)
+        .build();
+
+    let large_text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_10X20)
+        .text_color(TriColor::Chromatic)
+        .build();
+
+    let week_text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_7X13_BOLD)
+        .text_color(TriColor::Chromatic)
+        .build();
+
+    let scale = cell_scale(cell.size);
+    let top_left = align(cell, MIN_CELL_SIZE * scale, Align::Center, Align::Center);
+    let display = &mut Scaled::new(display, top_left, scale);
+    let content = Rectangle::new(Point::zero(), MIN_CELL_SIZE);
+    let origin = content.top_left;
+
+    draw_icon(display, forecast.weather, origin + Point::new(6, 3));
+    let _ = Text::new(
+        &forecast.date.to_string(),
+        origin + Point::new(48, 21),
+        large_text_style,
+    )
+    .draw(display);
+    draw_text(
+        display,
+        &forecast.week,
+        origin + Point::new(46, 38),
+        week_text_style,
+    );
+
+    let temperature = format!("{}-{}C", forecast.min_temp, forecast.max_temp);
+    let width = text_width(&temperature, text_style.font);
+    let x = align(content, Size::new(width, 0), Align::Center, Align::Start).x;
+    let _ = Text::new(&temperature, Point::new(x, origin.y + 53), text_style).draw(display);
+}
+
+/// The latest readings in the middle of `cell`, the place over the icon and temperature, and
+/// when they were taken with the past hour's rainfall below, scaled like the forecast cells
+fn draw_current_weather_cell<D: DrawTarget<Color = TriColor>>(
+    display: &mut D,
+    cell: Rectangle,
+    report: &WeatherReport,
+) {
+    let small_text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+        .text_color(TriColor::Black)
+        .build();
+
+    let place_text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+        .text_color(TriColor::Chromatic)
+        .build();
+
+    let large_text_style = MonoTextStyleBuilder::new()
+        .font(&embedded_graphics::mono_font::ascii::FONT_10X20)
+        .text_color(TriColor::Black)
+        .build();
+
+    let scale = cell_scale(cell.size);
+    let top_left = align(cell, MIN_CELL_SIZE * scale, Align::Center, Align::Center);
+    let display = &mut Scaled::new(display, top_left, scale);
+    let content = pad(
+        Rectangle::new(Point::zero(), MIN_CELL_SIZE),
+        Padding::all(2),
+    );
+    let origin = content.top_left;
+
+    let columns = (content.size.width / 6) as usize;
+    draw_text(
+        display,
+        &truncate_to_columns(&report.place, columns),
+        origin + Point::new(0, 8),
+        place_text_style,
+    );
+
+    draw_icon(display, report.weather, origin + Point::new(0, 10));
+
+    let temperature = format!("{}C", report.temp);
+    let width = text_width(&temperature, large_text_style.font);
+    let x = align(content, Size::new(width, 0), Align::End, Align::Start).x;
+    let _ = Text::new(&temperature, Point::new(x, origin.y + 33), large_text_style).draw(display);
+
+    let time = match report.rainfall {
+        Some(rainfall) if rainfall > 0 => format!("{} {}mm", report.update_time.clock(), rainfall),
+        _ => report.update_time.clock(),
+    };
+    let _ = Text::new(&time, origin + Point::new(0, 51), small_text_style).draw(display);
+}
+
+/// The layouts, laid out for the size of the panel and drawn onto its buffer by the firmware
+/// and onto a plain frame by the simulator. Keeps what the sidebar redraw needs from the
+/// last `draw_weather`.
+pub struct Screen {
+    size: Size,
+    layout: DashboardLayout,
+    warnings: Vec<WarningSignal>,
+    tip: Option<String>,
+    forecast_text: Option<String>,
+    current_weather: Option<WeatherReport>,
+    freshness: Freshness,
+    stale_after: Duration,
+    language: Language,
+}
+
+impl Screen {
+    /// For a panel `size` big as drawn, with its rotation applied
+    pub fn new(size: Size) -> Self {
+        Screen {
+            size,
+            layout: DashboardLayout::new(size, 0, false, false),
+            warnings: Vec::new(),
+            tip: None,
+            forecast_text: None,
+            current_weather: None,
+            freshness: Freshness::Fetched(None),
+            stale_after: Duration::MAX,
+            language: Language::English,
+        }
+    }
+
+    /// Of the panel as drawn
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Language of the texts that will be drawn, Chinese needs taller lines
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    /// Shown with the next redraw of the sidebar, for weather fetched again but unchanged
+    pub fn set_freshness(&mut self, freshness: Freshness) {
+        self.freshness = freshness;
+    }
+
+    /// Drawn in the first cell by the next `draw_weather`, `None` gives the cell back to the
+    /// forecast
+    pub fn set_current_weather(&mut self, report: Option<&WeatherReport>) {
+        self.current_weather = report.cloned();
+    }
+
+    /// Age after which fetched weather is marked as old
+    pub fn set_stale_after(&mut self, stale_after: Duration) {
+        self.stale_after = stale_after;
+    }
+
+    /// Characters per line of `draw_text_page`, a Chinese character takes two
+    pub fn text_page_columns(&self) -> usize {
+        (self.size.width.saturating_sub(8) / 6) as usize
+    }
+
+    /// Lines per page of `draw_text_page`
+    pub fn text_page_lines(&self) -> usize {
+        ((self.size.height as i32 - self.title_bar_height()) / self.line_height()).max(0) as usize
+    }
+
+    /// Whole times the sidebar and its readings are drawn larger than on the 2.9" panel
+    pub fn sidebar_scale(&self) -> u32 {
+        sidebar_scale(self.size)
+    }
+
+    /// Where `draw_reading` draws the indoor temperature
+    pub fn temperature_reading(&self) -> Rectangle {
+        self.reading(TEMPERATURE_READING)
+    }
+
+    /// Where `draw_reading` draws the indoor humidity
+    pub fn humidity_reading(&self) -> Rectangle {
+        self.reading(HUMIDITY_READING)
+    }
+
+    fn reading(&self, top_left: Point) -> Rectangle {
+        let scale = self.sidebar_scale();
+
+        Rectangle::new(
+            self.layout.sidebar.top_left + top_left * scale as i32,
+            READING_SIZE * scale,
+        )
+    }
+
+    fn line_height(&self) -> i32 {
+        match self.language {
+            Language::English => 10,
+            _ => 13,
+        }
+    }
+
+    fn title_bar_height(&self) -> i32 {
+        self.line_height() + 2
+    }
+
+    /// As many days of the forecast as fit left of the sidebar, after the current weather when
+    /// it was set, with today's description and the notice banner
+    pub fn draw_weather<D: DrawTarget<Color = TriColor>>(
+        &mut self,
+        display: &mut D,
+        weather_forcast: &[WeatherForecast],
+        warnings: &[WarningSignal],
+        tip: Option<&SpecialWeatherTip>,
+        forecast_text: Option<&LocalForecastText>,
+        freshness: Freshness,
+    ) {
+        _ = display.clear(TriColor::White);
+
+        self.warnings = warnings.to_vec();
+        self.tip = tip.map(|t| t.desc.clone());
+        self.forecast_text = forecast_text.map(|t| t.forecast_desc.clone());
+        self.freshness = freshness;
+        self.layout = DashboardLayout::new(
+            self.size,
+            weather_forcast.len(),
+            self.forecast_text.is_some(),
+            self.current_weather.is_some(),
+        );
+
+        if let (Some(cell), Some(report)) = (self.layout.current_weather, &self.current_weather) {
+            draw_current_weather_cell(display, cell, report);
+        }
+
+        for (&cell, forecast) in self.layout.cells.iter().zip(weather_forcast) {
+            draw_forecast_cell(display, cell, forecast);
+        }
+
+        self.draw_forecast_text(display);
+        self.draw_notice_banner(display);
+    }
+
+    /// Full screen text with a title bar, `lines` should be wrapped to `text_page_columns`
+    pub fn draw_text_page<D: DrawTarget<Color = TriColor>>(
+        &self,
+        display: &mut D,
+        title: &str,
+        lines: &[String],
+        page: usize,
+        page_count: usize,
+    ) {
+        let line_height = self.line_height();
+        let title_bar_height = self.title_bar_height();
+
+        _ = display.clear(TriColor::White);
+        _ = display.fill_solid(
+            &Rectangle::new(
+                Point::zero(),
+                Size::new(self.size.width, title_bar_height as u32),
+            ),
+            TriColor::Chromatic,
+        );
+
+        let title_text_style = MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::White)
+            .build();
+
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::Black)
+            .build();
+
+        let title_baseline = title_bar_height - 3;
+        draw_text(
+            display,
+            title,
+            Point::new(2, title_baseline),
+            title_text_style,
+        );
+        _ = Text::with_alignment(
+            &format!("{}/{}", page + 1, page_count),
+            Point::new(self.size.width as i32 - 2, title_baseline),
+            title_text_style,
+            Alignment::Right,
+        )
+        .draw(display);
+
+        for (i, line) in lines.iter().take(self.text_page_lines()).enumerate() {
+            let y = title_bar_height + line_height + i as i32 * line_height;
+            draw_text(display, line, Point::new(4, y), text_style);
+        }
+    }
+
+    /// Sidebar with the icons of the indoor readings and the update time, over the grid's
+    /// separators. `now` is seconds since the Unix epoch, to tell how old the weather is.
+    pub fn draw_sidebar<D: DrawTarget<Color = TriColor>>(&self, display: &mut D, now: i64) {
+        self.draw_base_frame(display, now);
+
+        let display = &mut Scaled::new(display, self.layout.sidebar.top_left, self.sidebar_scale());
+
+        let _ = Image::new(
+            &TemperatureHigh::new(TriColor::Chromatic),
+            Point::new(3, 12),
+        )
+        .draw(display);
+
+        let _ =
+            Image::new(&WateringSoil::new(TriColor::Chromatic), Point::new(3, 64)).draw(display);
+    }
+
+    /// The sidebar's background and the lines between the cells
+    fn draw_base_frame<D: DrawTarget<Color = TriColor>>(&self, display: &mut D, now: i64) {
+        let layout = &self.layout;
+        let height = self.size.height as i32;
+
+        _ = display.fill_solid(&layout.sidebar, TriColor::Black);
+
+        let black_link_style_thick = PrimitiveStyle::with_stroke(TriColor::Black, 2);
+        let black_link_style = PrimitiveStyle::with_stroke(TriColor::Black, 1);
+
+        let sidebar_x = layout.sidebar.top_left.x;
+        _ = Line::new(Point::new(sidebar_x, 0), Point::new(sidebar_x, height))
+            .draw_styled(&black_link_style_thick, display);
+
+        let grid_width = layout.grid.size.width as i32;
+        for y in layout.row_separators() {
+            _ = Line::new(Point::new(0, y), Point::new(grid_width, y))
+                .draw_styled(&black_link_style_thick, display);
+        }
+
+        let text_x = layout.forecast_text.map(|text| text.top_left.x);
+        for x in layout.column_separators().chain(text_x) {
+            _ = Line::new(Point::new(x, 0), Point::new(x, height))
+                .draw_styled(&black_link_style, display);
+        }
+
+        self.draw_notice_banner(display);
+        self.draw_update_footer(display, now);
+    }
+
+    /// Today's forecast description wrapped into the third column
+    fn draw_forecast_text<D: DrawTarget<Color = TriColor>>(&self, display: &mut D) {
+        let (Some(text), Some(area)) = (&self.forecast_text, self.layout.forecast_text) else {
+            return;
+        };
+        let area = pad(area, Padding::left(3));
+
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::Black)
+            .build();
+
+        let line_height = self.line_height();
+        let columns = (area.size.width / 6) as usize;
+        let max_lines = (area.size.height.saturating_sub(4) / line_height as u32) as usize;
+
+        for (i, line) in wrap_text_truncated(text, columns, max_lines)
+            .iter()
+            .enumerate()
+        {
+            let y = line_height - 1 + i as i32 * line_height;
+            draw_text(display, line, area.top_left + Point::new(0, y), text_style);
+        }
+    }
+
+    /// When the weather was fetched at the bottom of the sidebar, and below it "OLD" with the
+    /// age once it is older than `stale_after` or came from storage. Redrawn with the sidebar,
+    /// so the marker shows up even while refreshes keep failing.
+    fn draw_update_footer<D: DrawTarget<Color = TriColor>>(&self, display: &mut D, now: i64) {
+        let (time, is_stored) = match self.freshness {
+            Freshness::Fetched(time) => (time, false),
+            Freshness::Stored(time) => (time, true),
+        };
+        let age = time.and_then(|time| time.age(now));
+        let is_stale = is_stored || age.is_some_and(|age| age > self.stale_after);
+
+        let scale = self.sidebar_scale();
+        let display = &mut Scaled::new(display, self.layout.sidebar.top_left, scale);
+        // Baseline of the last line, the update time goes above it
+        let footer = Point::new(5, (self.size.height / scale) as i32 - 4);
+
+        if let Some(time) = time {
+            let text_style = MonoTextStyleBuilder::new()
+                .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+                .text_color(TriColor::White)
+                .build();
+            let label = self.language.pick(("UPD", "更新", "更新"));
+            let text = format!("{} {}", label, time.clock());
+
+            draw_text(display, &text, footer + Point::new(0, -14), text_style);
+        }
+
+        if is_stale {
+            let text_style = MonoTextStyleBuilder::new()
+                .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+                .text_color(TriColor::Chromatic)
+                .build();
+            let label = self.language.pick(("OLD", "舊", "旧"));
+            let text = match age {
+                Some(age) => format!("{} {}", label, format_age(age)),
+                None => label.to_owned(),
+            };
+
+            draw_text(display, &text, footer, text_style);
+        }
+    }
+
+    /// Chromatic strip over the row separator listing the warnings in force, followed by
+    /// an "i" marker and the start of the latest special weather tip
+    fn draw_notice_banner<D: DrawTarget<Color = TriColor>>(&self, display: &mut D) {
+        if self.warnings.is_empty() && self.tip.is_none() {
+            return;
+        }
+
+        const CHAR_WIDTH: i32 = 6;
+        let banner_width = self.layout.grid.size.width as i32;
+        // Centred on the first row separator, or at the bottom of a single row, tall enough
+        // for the font in use
+        let banner_height = self.line_height() + 1;
+        let top = match self.layout.row_separators().next() {
+            Some(y) => y - banner_height / 2,
+            None => self.size.height as i32 - banner_height,
+        };
+        let baseline = top + banner_height - 3;
+
+        _ = display.fill_solid(
+            &Rectangle::new(
+                Point::new(0, top),
+                Size::new(banner_width as u32, banner_height as u32),
+            ),
+            TriColor::Chromatic,
+        );
+
+        let banner_text_style = MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::White)
+            .build();
+
+        let marker_text_style = MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::Chromatic)
+            .build();
+
+        let mut x = 2;
+
+        if !self.warnings.is_empty() {
+            let text = self
+                .warnings
+                .iter()
+                .map(|w| w.label(self.language))
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let text = truncate_to_columns(&text, ((banner_width - x) / CHAR_WIDTH) as usize);
+
+            draw_text(display, &text, Point::new(x, baseline), banner_text_style);
+            x += (text_columns(&text) as i32 + 1) * CHAR_WIDTH;
+        }
+
+        if let Some(tip) = &self.tip {
+            if x + 9 + CHAR_WIDTH > banner_width {
+                return;
+            }
+
+            _ = display.fill_solid(
+                &Rectangle::new(Point::new(x, baseline - 7), Size::new(7, 9)),
+                TriColor::White,
+            );
+            _ = Text::new("i", Point::new(x + 1, baseline), marker_text_style).draw(display);
+            x += 9;
+
+            let text = truncate_to_columns(tip, ((banner_width - x) / CHAR_WIDTH) as usize);
+            draw_text(display, &text, Point::new(x, baseline), banner_text_style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::TriColorFrame;
+    use crate::hko_response::{LocalForecastResponse, WarningInfoResponse};
+    use crate::json::parse_json;
+    use crate::model::WarningStatement;
+    use crate::text_layout::wrap_text;
+
+    const FLW_TC_FIXTURE: &str = include_str!("../tests/fixtures/hko/flw_tc.json");
+    const WARNING_INFO_TC_FIXTURE: &str = include_str!("../tests/fixtures/hko/warningInfo_tc.json");
+
+    fn text_style() -> MonoTextStyle<'static, TriColor> {
+        MonoTextStyleBuilder::new()
+            .font(&embedded_graphics::mono_font::ascii::FONT_6X10)
+            .text_color(TriColor::Black)
+            .build()
+    }
+
+    /// `text` drawn at the start of a line, with where the next character would go
+    fn draw_line(text: &str) -> (TriColorFrame, Point) {
+        let mut frame = TriColorFrame::new(Size::new(296, 16));
+        let end = draw_text(&mut frame, text, Point::new(4, 13), text_style());
+
+        (frame, end)
+    }
+
+    fn is_drawn(frame: &TriColorFrame, area: Rectangle) -> bool {
+        area.points()
+            .any(|point| frame.pixel(point) != TriColor::White)
+    }
+
+    #[test]
+    fn missing_glyphs_keep_their_columns() {
+        // Private use, in no font
+        let (frame, end) = draw_line("a\u{E000}b");
+
+        assert_eq!(end, Point::new(4 + 4 * 6, 13));
+        assert!(is_drawn(
+            &frame,
+            Rectangle::new(Point::new(4 + 6, 0), Size::new(12, 16))
+        ));
+    }
+
+    /// Every line of the Chinese texts HKO sends is drawn, over as many columns as it was
+    /// wrapped to
+    #[test]
+    fn traditional_chinese_responses_are_drawn() {
+        let forecast: LocalForecastText = parse_json::<LocalForecastResponse>(FLW_TC_FIXTURE)
+            .unwrap()
+            .into();
+        let statements: Vec<WarningStatement> =
+            parse_json::<WarningInfoResponse>(WARNING_INFO_TC_FIXTURE)
+                .unwrap()
+                .details
+                .into_iter()
+                .map(WarningStatement::from)
+                .collect();
+
+        let paragraphs = [
+            &forecast.general_situation,
+            &forecast.forecast_desc,
+            &forecast.outlook,
+        ]
+        .into_iter()
+        .chain(statements.iter().flat_map(|statement| &statement.contents));
+        let columns = Screen::new(Size::new(296, 128)).text_page_columns();
+
+        for line in paragraphs.flat_map(|paragraph| wrap_text(paragraph, columns)) {
+            let (frame, end) = draw_line(&line);
+            let width = text_columns(&line) as u32 * 6;
+
+            assert_eq!(end.x, 4 + width as i32, "{}", line);
+            for (i, c) in line.chars().enumerate() {
+                let x = 4 + text_columns(&line.chars().take(i).collect::<String>()) as i32 * 6;
+                let cell = Rectangle::new(Point::new(x, 0), Size::new(6, 16));
+                if !c.is_whitespace() {
+                    assert!(is_drawn(&frame, cell), "{} of {}", c, line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn traditional_chinese_labels_are_drawn() {
+        let language = Language::TraditionalChinese;
+        let signals = [
+            WarningSignal::RainstormBlack,
+            WarningSignal::RainstormRed,
+            WarningSignal::RainstormAmber,
+            WarningSignal::Thunderstorm,
+            WarningSignal::Landslip,
+            WarningSignal::NorthernNewTerritoriesFlooding,
+            WarningSignal::Tsunami,
+            WarningSignal::StrongMonsoon,
+            WarningSignal::VeryHot,
+            WarningSignal::Cold,
+            WarningSignal::Frost,
+            WarningSignal::FireDangerRed,
+            WarningSignal::FireDangerYellow,
+        ];
+        let labels = ["更新", "舊", "警告", "天氣提示"];
+
+        for label in signals
+            .iter()
+            .map(|signal| signal.label(language))
+            .chain(labels)
+        {
+            let (frame, end) = draw_line(label);
+
+            assert_eq!(end.x, 4 + text_columns(label) as i32 * 6, "{}", label);
+            assert!(
+                is_drawn(&frame, Rectangle::new(Point::zero(), frame.size())),
+                "{}",
+                label
+            );
+        }
+    }
+
+    #[test]
+    fn readings_drop_decimals_when_too_wide() {
+        assert_eq!(fit_reading("27.5C"), "27.5C");
+        assert_eq!(fit_reading("-5.5C"), "-5.5C");
+        assert_eq!(fit_reading("100.0%"), "100%");
+        assert_eq!(fit_reading("-10.5C"), "-10C");
+        assert_eq!(fit_reading("100000"), "100000");
+    }
+}
@@ -0,0 +1,478 @@
+//! Every screen layout drawn from fixed data and compared pixel for pixel with the reference
+//! images in `tests/snapshots`. A mismatch fails with `<name>.actual.ppm` and `<name>.diff.ppm`
+//! written beside the reference, the diff shows the pixels that changed in magenta over a
+//! faded copy of the reference. After checking a change is intended, record the new
+//! references with `UPDATE_SNAPSHOTS=1 cargo test -p weather-core --features render snapshot`.
+//! A missing reference fails like a mismatch, with only `<name>.actual.ppm` written, so a
+//! layout can't pass without a reference that was looked at.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use epd_waveshare::color::TriColor;
+
+use crate::frame::TriColorFrame;
+use crate::hko_response::NineDayForecastResponse;
+use crate::json::parse_json;
+use crate::layout::DashboardLayout;
+use crate::model::{
+    DateTime, Language, LocalForecastText, NineDayForecast, SpecialWeatherTip, WarningSignal,
+    Weather, WeatherForecast, WeatherReport,
+};
+use crate::screen::{self, Freshness, Screen};
+use crate::text_layout::paginate;
+
+const FND_FIXTURE: &str = include_str!("../tests/fixtures/hko/fnd.json");
+
+/// Same color as the simulator's previews, so references can be opened side by side
+const CHROMATIC: Rgb888 = Rgb888::RED;
+
+fn snapshot_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots")
+}
+
+/// `reference` faded, with the pixels that differ in `actual` in magenta
+fn diff_image(reference: &[u8], actual: &[u8], header_len: usize) -> Vec<u8> {
+    let mut diff = reference[..header_len].to_vec();
+
+    for (expected, found) in reference[header_len..]
+        .chunks(3)
+        .zip(actual[header_len..].chunks(3))
+    {
+        if expected == found {
+            diff.extend(expected.iter().map(|&value| 192 + value / 4));
+        } else {
+            diff.extend_from_slice(&[255, 0, 255]);
+        }
+    }
+
+    diff
+}
+
+fn assert_snapshot(name: &str, frame: &TriColorFrame) {
+    let dir = snapshot_dir();
+    let reference_path = dir.join(format!("{}.ppm", name));
+    let actual_path = dir.join(format!("{}.actual.ppm", name));
+    let diff_path = dir.join(format!("{}.diff.ppm", name));
+    let actual = frame.to_ppm(CHROMATIC);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&reference_path, &actual).unwrap();
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+        eprintln!("Recorded {}", reference_path.display());
+        return;
+    }
+
+    let Ok(reference) = fs::read(&reference_path) else {
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&actual_path, &actual).unwrap();
+        panic!(
+            "{} has no reference, check {} and record it with UPDATE_SNAPSHOTS=1",
+            name,
+            actual_path.display()
+        );
+    };
+
+    if reference == actual {
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+        return;
+    }
+
+    fs::write(&actual_path, &actual).unwrap();
+
+    // The header is the same for frames of the same size, pixels are compared past it
+    let header_len = actual.len() - frame.size().width as usize * frame.size().height as usize * 3;
+    if reference.len() != actual.len() || reference[..header_len] != actual[..header_len] {
+        panic!(
+            "{} is not the size of {}",
+            actual_path.display(),
+            reference_path.display()
+        );
+    }
+
+    let diff = diff_image(&reference, &actual, header_len);
+    let changed = diff[header_len..]
+        .chunks(3)
+        .filter(|pixel| pixel == &[255, 0, 255])
+        .count();
+    fs::write(&diff_path, diff).unwrap();
+
+    panic!(
+        "{} pixels of {} changed, see {} and {}",
+        changed,
+        name,
+        actual_path.display(),
+        diff_path.display()
+    );
+}
+
+/// The 2.9" panel as drawn
+const SIZE: Size = Size::new(296, 128);
+
+fn new_frame() -> TriColorFrame {
+    TriColorFrame::new(SIZE)
+}
+
+fn new_screen() -> Screen {
+    Screen::new(SIZE)
+}
+
+fn draw_readings(screen: &Screen, frame: &mut TriColorFrame, temperature: &str, humidity: &str) {
+    screen::draw_reading(
+        &mut frame.translated(screen.temperature_reading().top_left),
+        temperature,
+        screen.sidebar_scale(),
+    );
+    screen::draw_reading(
+        &mut frame.translated(screen.humidity_reading().top_left),
+        humidity,
+        screen.sidebar_scale(),
+    );
+}
+
+fn nine_day_forecast() -> NineDayForecast {
+    parse_json::<NineDayForecastResponse>(FND_FIXTURE)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+fn forecast(date: u8, week: &str, min_temp: i8, max_temp: i8, weather: Weather) -> WeatherForecast {
+    WeatherForecast {
+        date,
+        week: week.to_owned(),
+        min_temp,
+        max_temp,
+        weather,
+        ..WeatherForecast::default()
+    }
+}
+
+/// 20 May 2024 11:30 in Hong Kong, when the fixture was issued
+fn update_time() -> DateTime {
+    nine_day_forecast().update_time
+}
+
+/// Twenty minutes after `update_time`
+fn now() -> i64 {
+    update_time().unix_time() + 20 * 60
+}
+
+/// The whole screen after a refresh, the forecast followed by the sidebar and its readings
+fn draw_dashboard(
+    screen: &mut Screen,
+    forecasts: &[WeatherForecast],
+    warnings: &[WarningSignal],
+    tip: Option<&SpecialWeatherTip>,
+    forecast_text: Option<&LocalForecastText>,
+    freshness: Freshness,
+) -> TriColorFrame {
+    let mut frame = TriColorFrame::new(screen.size());
+
+    screen.draw_weather(
+        &mut frame,
+        forecasts,
+        warnings,
+        tip,
+        forecast_text,
+        freshness,
+    );
+    screen.draw_sidebar(&mut frame, now());
+    draw_readings(screen, &mut frame, "27.5C", "68.0%");
+
+    frame
+}
+
+fn fetched() -> Freshness {
+    Freshness::Fetched(Some(update_time()))
+}
+
+#[test]
+fn forecast_grid() {
+    let forecasts = nine_day_forecast().forecasts;
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("forecast_grid", &frame);
+}
+
+/// Every day of the forecast fits on the larger panels
+#[test]
+fn forecast_grid_on_larger_panels() {
+    let forecast = nine_day_forecast();
+
+    for (name, size) in [
+        ("forecast_grid_4in2", Size::new(400, 300)),
+        ("forecast_grid_7in5", Size::new(800, 480)),
+    ] {
+        let frame = draw_dashboard(
+            &mut Screen::new(size),
+            &forecast.forecasts,
+            &[WarningSignal::Thunderstorm],
+            None,
+            None,
+            fetched(),
+        );
+
+        assert_snapshot(name, &frame);
+    }
+}
+
+/// The readings take the first cell, ahead of five days or of three beside the description
+#[test]
+fn current_weather() {
+    let forecast = nine_day_forecast();
+    let report = WeatherReport {
+        place: "Sham Shui Po".to_owned(),
+        temp: 28,
+        weather: Weather::HeavyRain,
+        update_time: update_time(),
+        rainfall: Some(12),
+    };
+    let forecast_text = LocalForecastText {
+        general_situation: String::new(),
+        tc_info: String::new(),
+        fire_danger_warning: String::new(),
+        forecast_period: "Weather forecast for today".to_owned(),
+        forecast_desc: "Mainly cloudy with a few showers.".to_owned(),
+        outlook: String::new(),
+        update_time: update_time(),
+    };
+
+    for (name, forecast_text) in [
+        ("current_weather", None),
+        ("current_weather_with_forecast_text", Some(&forecast_text)),
+    ] {
+        let mut screen = new_screen();
+        screen.set_current_weather(Some(&report));
+
+        let frame = draw_dashboard(
+            &mut screen,
+            &forecast.forecasts,
+            &[],
+            None,
+            forecast_text,
+            fetched(),
+        );
+
+        assert_snapshot(name, &frame);
+    }
+}
+
+#[test]
+fn forecast_grid_with_forecast_text() {
+    let forecast = nine_day_forecast();
+    let forecast_text = LocalForecastText {
+        general_situation: forecast.general_situation.clone(),
+        tc_info: String::new(),
+        fire_danger_warning: String::new(),
+        forecast_period: "Weather forecast for today".to_owned(),
+        forecast_desc: "Mainly cloudy with a few showers and isolated squally \
+                        thunderstorms. Hot with sunny intervals during the day."
+            .to_owned(),
+        outlook: String::new(),
+        update_time: forecast.update_time,
+    };
+
+    let frame = draw_dashboard(
+        &mut new_screen(),
+        &forecast.forecasts,
+        &[],
+        None,
+        Some(&forecast_text),
+        fetched(),
+    );
+
+    assert_snapshot("forecast_grid_with_forecast_text", &frame);
+}
+
+#[test]
+fn notice_banner() {
+    let forecasts = nine_day_forecast().forecasts;
+    let warnings = [
+        WarningSignal::RainstormAmber,
+        WarningSignal::Thunderstorm,
+        WarningSignal::FireDangerYellow,
+    ];
+    let tip = SpecialWeatherTip {
+        desc: "Heavy rain may bring flooding to low-lying areas.".to_owned(),
+        update_time: update_time(),
+    };
+
+    let frame = draw_dashboard(
+        &mut new_screen(),
+        &forecasts,
+        &warnings,
+        Some(&tip),
+        None,
+        fetched(),
+    );
+
+    assert_snapshot("notice_banner", &frame);
+}
+
+#[test]
+fn indoor_sidebar() {
+    let mut frame = new_frame();
+    let mut screen = new_screen();
+    screen.set_freshness(fetched());
+
+    screen.draw_sidebar(&mut frame, now());
+    draw_readings(&screen, &mut frame, "-5.5C", "100.0%");
+
+    assert_snapshot("indoor_sidebar", &frame);
+}
+
+/// Refreshes have kept failing for longer than `stale_after`
+#[test]
+fn stale_weather() {
+    let forecasts = nine_day_forecast().forecasts;
+    let mut screen = new_screen();
+    screen.set_stale_after(Duration::from_secs(10 * 60));
+
+    let frame = draw_dashboard(&mut screen, &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("stale_weather", &frame);
+}
+
+/// Loaded from storage at boot as nothing could be fetched
+#[test]
+fn stored_weather() {
+    let forecasts = nine_day_forecast().forecasts;
+    let freshness = Freshness::Stored(Some(update_time()));
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, freshness);
+
+    assert_snapshot("stored_weather", &frame);
+}
+
+/// Fetched before SNTP set the clock, so there is no update time to show
+#[test]
+fn clock_not_set() {
+    let forecasts = nine_day_forecast().forecasts;
+    let freshness = Freshness::Fetched(None);
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, freshness);
+
+    assert_snapshot("clock_not_set", &frame);
+}
+
+#[test]
+fn negative_temperatures() {
+    let forecasts = [
+        forecast(1, "MON", -12, -3, Weather::Cold),
+        forecast(2, "TUE", -8, 0, Weather::Cold),
+        forecast(3, "WED", -1, 4, Weather::Cool),
+        forecast(4, "THU", -10, -10, Weather::Unknow),
+        forecast(5, "FRI", -40, 45, Weather::Windy),
+        forecast(6, "SAT", 0, 0, Weather::Fog),
+    ];
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("negative_temperatures", &frame);
+}
+
+/// One and two digit dates side by side, across the end of a month
+#[test]
+fn dates_across_month_end() {
+    let forecasts = [
+        forecast(29, "WED", 20, 24, Weather::Sunny),
+        forecast(30, "THU", 21, 25, Weather::SunnyPeriods),
+        forecast(31, "FRI", 22, 26, Weather::Rain),
+        forecast(1, "SAT", 22, 27, Weather::HeavyRain),
+        forecast(2, "SUN", 23, 28, Weather::Thunderstorm),
+        forecast(10, "MON", 23, 28, Weather::FineNight),
+    ];
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("dates_across_month_end", &frame);
+}
+
+/// A short forecast is drawn in fewer, larger cells
+#[test]
+fn missing_days() {
+    let forecasts = [forecast(21, "TUE", 25, 29, Weather::Rain)];
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("missing_days", &frame);
+}
+
+/// Days a provider had nothing for come through as the default forecast, dated 0 with "---"
+/// for the day and the unknown weather icon
+#[test]
+fn default_days() {
+    let forecasts = [
+        forecast(21, "TUE", 25, 29, Weather::Rain),
+        WeatherForecast::default(),
+        forecast(23, "THU", 26, 30, Weather::Cloudly),
+        WeatherForecast::default(),
+        WeatherForecast::default(),
+        WeatherForecast::default(),
+    ];
+
+    let frame = draw_dashboard(&mut new_screen(), &forecasts, &[], None, None, fetched());
+
+    assert_snapshot("default_days", &frame);
+}
+
+#[test]
+fn text_page() {
+    let screen = new_screen();
+    let general_situation = nine_day_forecast().general_situation;
+    let pages = paginate(
+        std::slice::from_ref(&general_situation),
+        screen.text_page_columns(),
+        screen.text_page_lines(),
+    );
+    let mut frame = new_frame();
+
+    screen.draw_text_page(&mut frame, "WEATHER TIP", &pages[0], 0, pages.len());
+
+    assert_snapshot("text_page", &frame);
+}
+
+#[test]
+fn chinese_text_page() {
+    let mut screen = new_screen();
+    screen.set_language(Language::TraditionalChinese);
+    let lines = paginate(
+        &["一道活躍低壓槽會在未來一兩日為廣東沿岸帶來驟雨及狂風雷暴。".to_owned()],
+        screen.text_page_columns(),
+        screen.text_page_lines(),
+    );
+    let mut frame = new_frame();
+
+    screen.draw_text_page(&mut frame, "天氣提示", &lines[0], 0, 1);
+
+    assert_snapshot("chinese_text_page", &frame);
+}
+
+/// The widest values of each field must not reach into the next column, where they used to
+/// run over the separator
+#[test]
+fn cells_stay_inside_their_columns() {
+    let widest = forecast(28, "WED", -40, -40, Weather::Thunderstorm);
+    let forecasts = vec![widest; 6];
+    let mut frame = new_frame();
+
+    new_screen().draw_weather(&mut frame, &forecasts, &[], None, None, fetched());
+
+    for x in DashboardLayout::new(SIZE, forecasts.len(), false, false).column_separators() {
+        for y in 0..128 {
+            assert_eq!(
+                frame.pixel(Point::new(x, y)),
+                TriColor::White,
+                "cell drawn over the separator at ({}, {})",
+                x,
+                y
+            );
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+#[cfg(test)]
+use alloc::{borrow::ToOwned, collections::BTreeMap, string::String};
+use core::fmt;
+
+use crate::last_weather::LastWeather;
+
+/// NVS keys are limited to 15 characters
+const LAST_WEATHER_KEY: &str = "last_weather";
+
+/// Values that survive a reboot, NVS on the device
+pub trait Storage {
+    /// Also what a `LastWeather` that can't be serialised is reported as
+    type Error: fmt::Debug + From<serde_json::Error>;
+
+    fn load(&mut self, key: &str) -> Result<Option<Vec<u8>>, Self::Error>;
+    fn save(&mut self, key: &str, value: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// `None` when nothing was saved or it can't be read back, e.g. after a format change
+pub fn load_last_weather<S: Storage>(storage: &mut S) -> Option<LastWeather> {
+    let bytes = match storage.load(LAST_WEATHER_KEY) {
+        Ok(bytes) => bytes?,
+        Err(err) => {
+            log::warn!("Failed to load the last weather: {:?}", err);
+            return None;
+        }
+    };
+
+    LastWeather::from_bytes(&bytes)
+        .map_err(|err| log::warn!("Failed to parse the last weather: {}", err))
+        .ok()
+}
+
+pub fn save_last_weather<S: Storage>(
+    storage: &mut S,
+    weather: &LastWeather,
+) -> Result<(), S::Error> {
+    storage.save(LAST_WEATHER_KEY, &weather.to_bytes()?)
+}
+
+/// Keeps the values in memory, for the host
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    pub values: BTreeMap<String, Vec<u8>>,
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    type Error = serde_json::Error;
+
+    fn load(&mut self, key: &str) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, value: &[u8]) -> Result<(), Self::Error> {
+        self.values.insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WeatherForecast;
+    use alloc::vec;
+
+    fn last_weather(saved_at: i64) -> LastWeather {
+        LastWeather {
+            saved_at,
+            forecasts: vec![WeatherForecast::default()],
+            current_weather: None,
+        }
+    }
+
+    #[test]
+    fn saved_weather_is_loaded() {
+        let mut storage = MemoryStorage::default();
+
+        save_last_weather(&mut storage, &last_weather(1)).unwrap();
+        save_last_weather(&mut storage, &last_weather(2)).unwrap();
+
+        assert_eq!(load_last_weather(&mut storage), Some(last_weather(2)));
+    }
+
+    #[test]
+    fn nothing_saved_loads_nothing() {
+        assert_eq!(load_last_weather(&mut MemoryStorage::default()), None);
+    }
+
+    #[test]
+    fn corrupt_weather_loads_nothing() {
+        let mut storage = MemoryStorage::default();
+        storage
+            .values
+            .insert(LAST_WEATHER_KEY.to_owned(), b"{\"saved_at\":".to_vec());
+
+        assert_eq!(load_last_weather(&mut storage), None);
+    }
+}
@@ -0,0 +1,282 @@
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::mem;
+
+/// Columns taken by a character, CJK glyphs are twice as wide as ASCII ones
+pub fn char_columns(c: char) -> usize {
+    if c.is_ascii() {
+        1
+    } else {
+        2
+    }
+}
+
+pub fn text_columns(text: &str) -> usize {
+    text.chars().map(char_columns).sum()
+}
+
+/// Longest prefix of `text` that fits in `columns`
+pub fn truncate_to_columns(text: &str, columns: usize) -> String {
+    let mut width = 0;
+
+    text.chars()
+        .take_while(|&c| {
+            width += char_columns(c);
+            width <= columns
+        })
+        .collect()
+}
+
+/// CJK punctuation that must not start a line, kept with the character before it
+const CLOSING_PUNCTUATION: &[char] = &[
+    '，', '。', '、', '；', '：', '？', '！', '）', '」', '』', '》', '〉',
+];
+
+/// Splits a word into pieces a line can break between: runs of ASCII stay together while
+/// every CJK character stands alone, as Chinese text has no spaces between words
+fn break_points(word: &str) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    let mut ascii_run = String::new();
+
+    for c in word.chars() {
+        if c.is_ascii() {
+            ascii_run.push(c);
+        } else {
+            if !ascii_run.is_empty() {
+                pieces.push(mem::take(&mut ascii_run));
+            }
+            match pieces.last_mut() {
+                Some(last) if CLOSING_PUNCTUATION.contains(&c) => last.push(c),
+                _ => pieces.push(c.to_string()),
+            }
+        }
+    }
+
+    if !ascii_run.is_empty() {
+        pieces.push(ascii_run);
+    }
+
+    pieces
+}
+
+/// Greedy word wrap for mono fonts, `columns` is the number of ASCII characters that fit on a
+/// line. Words longer than a line are split.
+pub fn wrap_text(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for word in text.split_whitespace() {
+        for (i, mut piece) in break_points(word).into_iter().enumerate() {
+            // Only whole words are separated by a space
+            let space = usize::from(i == 0 && line_len > 0);
+
+            if line_len > 0 && line_len + space + text_columns(&piece) > columns {
+                lines.push(mem::take(&mut line));
+                line_len = 0;
+            }
+
+            while text_columns(&piece) > columns {
+                let head = truncate_to_columns(&piece, columns);
+                if head.is_empty() {
+                    break;
+                }
+                piece = piece[head.len()..].to_owned();
+                lines.push(head);
+            }
+
+            if line_len > 0 && space > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+            line_len += text_columns(&piece);
+            line.push_str(&piece);
+        }
+    }
+
+    if line_len > 0 {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Like `wrap_text` but keeps at most `max_lines`, ending with ".." when text was cut off
+pub fn wrap_text_truncated(text: &str, columns: usize, max_lines: usize) -> Vec<String> {
+    let mut lines = wrap_text(text, columns);
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            *last = truncate_to_columns(last, columns.saturating_sub(2)) + "..";
+        }
+    }
+
+    lines
+}
+
+/// Wrap every paragraph and split the lines into pages, paragraphs are separated by an empty
+/// line. A page holds at least one line, even when `lines_per_page` is 0.
+pub fn paginate(paragraphs: &[String], columns: usize, lines_per_page: usize) -> Vec<Vec<String>> {
+    let lines_per_page = lines_per_page.max(1);
+    let mut lines: Vec<String> = Vec::new();
+
+    for paragraph in paragraphs {
+        let wrapped = wrap_text(paragraph, columns);
+        if wrapped.is_empty() {
+            continue;
+        }
+
+        // No separator needed when the paragraph starts a new page
+        if lines.len() % lines_per_page != 0 {
+            lines.push(String::new());
+        }
+        lines.extend(wrapped);
+    }
+
+    lines
+        .chunks(lines_per_page)
+        .map(|page| page.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|&text| text.to_owned()).collect()
+    }
+
+    #[test]
+    fn truncate_keeps_the_prefix_that_fits() {
+        assert_eq!(truncate_to_columns("abc", 2), "ab");
+        assert_eq!(truncate_to_columns("abc", 3), "abc");
+        assert_eq!(truncate_to_columns("abc", 0), "");
+    }
+
+    #[test]
+    fn ascii_words_are_kept_whole() {
+        assert_eq!(break_points("squally"), ["squally"]);
+        assert!(break_points("").is_empty());
+    }
+
+    #[test]
+    fn cjk_characters_take_two_columns() {
+        assert_eq!(char_columns('a'), 1);
+        assert_eq!(char_columns('星'), 2);
+        assert_eq!(char_columns('。'), 2);
+        assert_eq!(text_columns("8號風球"), 7);
+        assert_eq!(truncate_to_columns("星期一", 5), "星期");
+    }
+
+    #[test]
+    fn cjk_characters_stand_alone() {
+        assert_eq!(break_points("8號風球"), ["8", "號", "風", "球"]);
+        assert_eq!(break_points("T8NE信號"), ["T8NE", "信", "號"]);
+    }
+
+    #[test]
+    fn mixed_cjk_and_ascii_lines() {
+        assert_eq!(wrap_text("烈風8號信號", 6), ["烈風8", "號信號"]);
+        assert_eq!(wrap_text("最高 32 度", 8), ["最高 32", "度"]);
+    }
+
+    #[test]
+    fn closing_punctuation_doesnt_start_a_line() {
+        assert_eq!(break_points("有雨。"), ["有", "雨。"]);
+        assert_eq!(wrap_text("天晴。有雨。", 6), ["天晴。", "有雨。"]);
+        assert_eq!(wrap_text("天晴。有雨。", 4), ["天", "晴。", "有", "雨。"]);
+    }
+
+    #[test]
+    fn wide_character_at_the_column_limit() {
+        assert_eq!(wrap_text("ab星", 4), ["ab星"]);
+        assert_eq!(wrap_text("ab星", 3), ["ab", "星"]);
+        assert_eq!(truncate_to_columns("ab星", 3), "ab");
+        // Wider than a line, it is kept whole instead of looping
+        assert_eq!(wrap_text("星", 1), ["星"]);
+    }
+
+    #[test]
+    fn pieces_are_split_between_characters() {
+        assert_eq!(wrap_text("颱風信號。", 3), ["颱", "風", "信", "號", "。"]);
+        assert_eq!(wrap_text_truncated("颱風信號現正生效", 8, 1), ["颱風信.."]);
+    }
+
+    #[test]
+    fn exact_fit_stays_on_one_line() {
+        assert_eq!(wrap_text("abc de", 6), ["abc de"]);
+        assert_eq!(wrap_text("abc def", 6), ["abc", "def"]);
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        assert_eq!(
+            wrap_text("a abcdefghij b", 4),
+            ["a", "abcd", "efgh", "ij b"]
+        );
+    }
+
+    #[test]
+    fn empty_text_has_no_lines() {
+        assert!(wrap_text("", 10).is_empty());
+        assert!(wrap_text(" \n ", 10).is_empty());
+    }
+
+    #[test]
+    fn text_cut_off_ends_with_dots() {
+        assert_eq!(wrap_text_truncated("one two", 7, 2), ["one two"]);
+        assert_eq!(
+            wrap_text_truncated("one two three four", 7, 2),
+            ["one two", "three.."]
+        );
+        assert_eq!(wrap_text_truncated("one two threefold", 7, 1), ["one t.."]);
+    }
+
+    #[test]
+    fn lines_overflow_onto_more_pages() {
+        let pages = paginate(&strings(&["aaa bbb ccc ddd eee"]), 3, 2);
+
+        assert_eq!(
+            pages,
+            [
+                strings(&["aaa", "bbb"]),
+                strings(&["ccc", "ddd"]),
+                strings(&["eee"])
+            ]
+        );
+    }
+
+    #[test]
+    fn paragraphs_are_separated_except_at_page_starts() {
+        assert_eq!(
+            paginate(&strings(&["aaa", "bbb"]), 3, 3),
+            [strings(&["aaa", "", "bbb"])]
+        );
+        assert_eq!(
+            paginate(&strings(&["aaa bbb", "ccc"]), 3, 2),
+            [strings(&["aaa", "bbb"]), strings(&["ccc"])]
+        );
+    }
+
+    #[test]
+    fn empty_paragraphs_are_skipped() {
+        assert_eq!(
+            paginate(&strings(&["", "aaa", "  ", "bbb"]), 3, 5),
+            [strings(&["aaa", "", "bbb"])]
+        );
+        assert!(paginate(&[], 3, 5).is_empty());
+    }
+
+    #[test]
+    fn a_page_holds_a_line_when_none_fit() {
+        assert_eq!(
+            paginate(&strings(&["aaa bbb"]), 3, 0),
+            [strings(&["aaa"]), strings(&["bbb"])]
+        );
+    }
+}
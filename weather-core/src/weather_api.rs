@@ -0,0 +1,381 @@
+use alloc::{borrow::ToOwned, boxed::Box, collections::BTreeMap, format, string::String, vec::Vec};
+use core::any::Any;
+
+use serde::de::DeserializeOwned;
+
+use crate::hko;
+use crate::hko_response::{
+    CurrentWeatherResponse, LocalForecastResponse, NineDayForecastResponse,
+    SpecialWeatherTipsResponse, WarningInfoResponse, WarningSummaryResponse,
+};
+use crate::http::HttpClient;
+use crate::model::*;
+use crate::weather_provider::WeatherProvider;
+
+const HKO_API_URL: &str = "https://data.weather.gov.hk/weatherAPI/opendata/weather.php";
+
+const NINE_DAY_FORECAST: &str = "fnd";
+const CURRENT_WEATHER_REPORT: &str = "rhrread";
+const WARNING_SUMMARY: &str = "warnsum";
+const WARNING_INFO: &str = "warningInfo";
+const SPECIAL_WEATHER_TIPS: &str = "swt";
+const LOCAL_FORECAST: &str = "flw";
+
+/// Language of the HKO responses and the stations `fetch_current_weather` reports on
+#[derive(Clone)]
+pub struct WeatherApiConfig {
+    pub language: Language,
+    /// Place name in the `rhrread` temperature list, in the configured language
+    pub temperature_station: String,
+    /// District name in the `rhrread` rainfall list, in the configured language
+    pub rainfall_district: String,
+}
+
+/// What was mapped from a response, with the validators to revalidate it with
+struct CachedModel {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    model: Box<dyn Any>,
+}
+
+pub struct WeatherApi<C: HttpClient> {
+    http_client: C,
+    config: WeatherApiConfig,
+    /// Keyed by data type, only responses that came with a validator
+    cache: BTreeMap<&'static str, CachedModel>,
+}
+
+impl WeatherApiConfig {
+    /// Sham Shui Po station and district, named in `language`
+    pub fn new(language: Language) -> Self {
+        let place = match language {
+            Language::English => "Sham Shui Po",
+            Language::TraditionalChinese | Language::SimplifiedChinese => "深水埗",
+        };
+
+        Self {
+            language,
+            temperature_station: String::from(place),
+            rainfall_district: String::from(place),
+        }
+    }
+}
+
+impl Default for WeatherApiConfig {
+    fn default() -> Self {
+        Self::new(Language::English)
+    }
+}
+
+impl<C> WeatherApi<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, config: WeatherApiConfig) -> Self {
+        Self {
+            http_client: client,
+            config,
+            cache: BTreeMap::new(),
+        }
+    }
+
+    /// Conditional request when the last response had an `ETag` or `Last-Modified`. Only
+    /// what `map` makes of the response is kept, a `304` is answered with a copy of it instead
+    /// of downloading and parsing the response again.
+    fn get_mapped<T, M>(
+        &mut self,
+        data_type: &'static str,
+        map: impl FnOnce(T, &WeatherApiConfig) -> Result<M, ApiError>,
+    ) -> Result<M, ApiError>
+    where
+        T: DeserializeOwned,
+        M: Clone + 'static,
+    {
+        let url = format!(
+            "{}?dataType={}&lang={}",
+            HKO_API_URL,
+            data_type,
+            self.config.language.api_code()
+        );
+
+        let mut headers = Vec::new();
+        if let Some(cached) = self.cache.get(data_type) {
+            if let Some(etag) = cached.etag.as_deref() {
+                headers.push(("If-None-Match", etag));
+            }
+            if let Some(last_modified) = cached.last_modified.as_deref() {
+                headers.push(("If-Modified-Since", last_modified));
+            }
+        }
+
+        let response = self.http_client.get_json::<T>(&url, &headers)?;
+        if response.status == 304 {
+            let cached = self.cache.get(data_type);
+            if let Some(model) = cached.and_then(|cached| cached.model.downcast_ref::<M>()) {
+                return Ok(model.clone());
+            }
+        }
+
+        let response = response.error_for_status()?;
+        let etag = response.header("ETag").map(str::to_owned);
+        let last_modified = response.header("Last-Modified").map(str::to_owned);
+        let model = map(response.body.ok_or(ApiError::EmptyResponse)?, &self.config)?;
+
+        if etag.is_some() || last_modified.is_some() {
+            self.cache.insert(
+                data_type,
+                CachedModel {
+                    etag,
+                    last_modified,
+                    model: Box::new(model.clone()),
+                },
+            );
+        } else {
+            self.cache.remove(data_type);
+        }
+
+        Ok(model)
+    }
+
+    pub fn fetch_current_weather(&mut self) -> Result<WeatherReport, ApiError> {
+        self.get_mapped(
+            CURRENT_WEATHER_REPORT,
+            |response: CurrentWeatherResponse, config| {
+                log::info!("{}", response.update_time);
+
+                hko::current_weather(
+                    response,
+                    &config.temperature_station,
+                    &config.rainfall_district,
+                )
+            },
+        )
+    }
+
+    pub fn fetch_nine_day_forecast(&mut self) -> Result<NineDayForecast, ApiError> {
+        self.get_mapped(NINE_DAY_FORECAST, |response: NineDayForecastResponse, _| {
+            response.try_into()
+        })
+    }
+
+    /// Warnings currently in force, most important first
+    pub fn fetch_warning_summary(&mut self) -> Result<Vec<WarningSignal>, ApiError> {
+        self.get_mapped(WARNING_SUMMARY, |response: WarningSummaryResponse, _| {
+            Ok(hko::warning_signals(&response))
+        })
+    }
+
+    pub fn fetch_warning_info(&mut self) -> Result<Vec<WarningStatement>, ApiError> {
+        self.get_mapped(WARNING_INFO, |response: WarningInfoResponse, _| {
+            Ok(response
+                .details
+                .into_iter()
+                .map(WarningStatement::from)
+                .collect())
+        })
+    }
+
+    pub fn fetch_special_weather_tips(&mut self) -> Result<Vec<SpecialWeatherTip>, ApiError> {
+        self.get_mapped(
+            SPECIAL_WEATHER_TIPS,
+            |response: SpecialWeatherTipsResponse, _| {
+                Ok(response
+                    .swt
+                    .into_iter()
+                    .map(SpecialWeatherTip::from)
+                    .collect())
+            },
+        )
+    }
+
+    pub fn fetch_local_forecast_text(&mut self) -> Result<LocalForecastText, ApiError> {
+        self.get_mapped(LOCAL_FORECAST, |response: LocalForecastResponse, _| {
+            Ok(response.into())
+        })
+    }
+}
+
+impl<C> WeatherProvider for WeatherApi<C>
+where
+    C: HttpClient,
+{
+    fn fetch_forecast(&mut self) -> Result<Vec<WeatherForecast>, ApiError> {
+        self.fetch_nine_day_forecast()
+            .map(|forecast| forecast.forecasts)
+    }
+
+    fn fetch_current_weather(&mut self) -> Result<WeatherReport, ApiError> {
+        WeatherApi::fetch_current_weather(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{CannedHttpClient, HttpResponse};
+
+    const FND_FIXTURE: &str = include_str!("../tests/fixtures/hko/fnd.json");
+    const RHRREAD_FIXTURE: &str = include_str!("../tests/fixtures/hko/rhrread.json");
+    const WARNSUM_FIXTURE: &str = include_str!("../tests/fixtures/hko/warnsum.json");
+
+    const NINE_DAY_FORECAST_RESPONSE: &str = r#"{
+        "generalSituation": "An area of low pressure will bring showers.",
+        "weatherForecast": [{
+            "forecastDate": "20240520",
+            "week": "Monday",
+            "forecastWind": "South force 3 to 4.",
+            "forecastWeather": "Mainly cloudy with a few showers.",
+            "forecastMaxtemp": {"value": 30, "unit": "C"},
+            "forecastMintemp": {"value": 26, "unit": "C"},
+            "forecastMaxrh": {"value": 95, "unit": "percent"},
+            "forecastMinrh": {"value": 70, "unit": "percent"},
+            "ForecastIcon": 54,
+            "PSR": "Medium Low"
+        }],
+        "updateTime": "2024-05-20T11:30:00+08:00",
+        "seaTemp": {"place": "North Point", "value": 27, "unit": "C", "recordTime": "2024-05-20T07:00:00+08:00"},
+        "soilTemp": []
+    }"#;
+
+    #[test]
+    fn forecast_through_provider() {
+        let mut api = WeatherApi::new(
+            CannedHttpClient::new(NINE_DAY_FORECAST_RESPONSE),
+            WeatherApiConfig::default(),
+        );
+
+        let forecasts = api.fetch_forecast().unwrap();
+        assert_eq!(forecasts.len(), 1);
+        assert_eq!(forecasts[0].date, 20);
+        assert_eq!(forecasts[0].week, "MON");
+        assert_eq!((forecasts[0].min_temp, forecasts[0].max_temp), (26, 30));
+        assert_eq!(forecasts[0].weather, Weather::SunnyIntervalsWithShowers);
+        assert_eq!(forecasts[0].psr, Psr::MediumLow);
+
+        assert!(api.http_client.requests[0]
+            .0
+            .ends_with("?dataType=fnd&lang=en"));
+    }
+
+    fn not_modified() -> HttpResponse {
+        HttpResponse {
+            status: 304,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn not_modified_reuses_the_mapped_model() {
+        let mut client = CannedHttpClient::new(NINE_DAY_FORECAST_RESPONSE);
+        client.response.headers = vec![
+            (String::from("ETag"), String::from("\"fnd-1130\"")),
+            (
+                String::from("Last-Modified"),
+                String::from("Mon, 20 May 2024 03:30:00 GMT"),
+            ),
+        ];
+        let mut api = WeatherApi::new(client, WeatherApiConfig::default());
+
+        let first = api.fetch_forecast().unwrap();
+        assert!(api.http_client.requests[0].1.is_empty());
+
+        api.http_client.response = not_modified();
+        assert_eq!(api.fetch_forecast().unwrap(), first);
+
+        let headers = &api.http_client.requests[1].1;
+        assert!(headers.contains(&(String::from("If-None-Match"), String::from("\"fnd-1130\""))));
+        assert!(headers.contains(&(
+            String::from("If-Modified-Since"),
+            String::from("Mon, 20 May 2024 03:30:00 GMT")
+        )));
+    }
+
+    #[test]
+    fn validators_are_kept_per_endpoint() {
+        let mut client = CannedHttpClient::new(NINE_DAY_FORECAST_RESPONSE);
+        client.response.headers = vec![(String::from("ETag"), String::from("\"fnd-1130\""))];
+        let mut api = WeatherApi::new(client, WeatherApiConfig::default());
+        api.fetch_forecast().unwrap();
+
+        // Nothing cached for the tips, so a 304 for them can't be answered
+        api.http_client.response = not_modified();
+        let result = api.fetch_special_weather_tips();
+
+        assert!(matches!(result, Err(ApiError::HttpStatus(304))));
+        assert!(api.http_client.requests[1].1.is_empty());
+    }
+
+    #[test]
+    fn error_status_is_not_parsed() {
+        let mut client = CannedHttpClient::new("<html>Service Unavailable</html>");
+        client.response.status = 503;
+        let mut api = WeatherApi::new(client, WeatherApiConfig::default());
+
+        assert!(matches!(
+            api.fetch_nine_day_forecast(),
+            Err(ApiError::HttpStatus(503))
+        ));
+    }
+
+    /// Answers with `body` and an ETag named after its data type
+    fn respond_with(api: &mut WeatherApi<CannedHttpClient>, data_type: &str, body: &str) {
+        api.http_client.response = HttpResponse {
+            status: 200,
+            headers: vec![(String::from("ETag"), format!("\"{}-1\"", data_type))],
+            body: String::from(body),
+        };
+    }
+
+    fn if_none_match(request: &(String, Vec<(String, String)>)) -> Option<&str> {
+        request
+            .1
+            .iter()
+            .find(|(name, _)| name == "If-None-Match")
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn fixtures_through_the_api() {
+        let mut api = WeatherApi::new(CannedHttpClient::new(""), WeatherApiConfig::default());
+
+        respond_with(&mut api, "fnd", FND_FIXTURE);
+        let forecast = api.fetch_nine_day_forecast().unwrap();
+        respond_with(&mut api, "rhrread", RHRREAD_FIXTURE);
+        let report = api.fetch_current_weather().unwrap();
+        respond_with(&mut api, "warnsum", WARNSUM_FIXTURE);
+        let warnings = api.fetch_warning_summary().unwrap();
+
+        assert_eq!(forecast.forecasts.len(), 9);
+        assert_eq!(forecast.forecasts[0].weather, Weather::Rain);
+        assert_eq!((report.place.as_str(), report.temp), ("Sham Shui Po", 25));
+        assert_eq!(report.rainfall, Some(7));
+        assert_eq!(
+            warnings,
+            [
+                WarningSignal::RainstormAmber,
+                WarningSignal::Thunderstorm,
+                WarningSignal::FireDangerYellow
+            ]
+        );
+
+        let urls: Vec<_> = api.http_client.requests.iter().map(|r| &r.0).collect();
+        assert!(urls[0].ends_with("?dataType=fnd&lang=en"));
+        assert!(urls[1].ends_with("?dataType=rhrread&lang=en"));
+        assert!(urls[2].ends_with("?dataType=warnsum&lang=en"));
+
+        // Every endpoint is revalidated with its own ETag and answered from the cache
+        api.http_client.response = not_modified();
+        assert_eq!(
+            api.fetch_nine_day_forecast().unwrap().forecasts,
+            forecast.forecasts
+        );
+        assert_eq!(api.fetch_current_weather().unwrap(), report);
+        assert_eq!(api.fetch_warning_summary().unwrap(), warnings);
+
+        let requests = &api.http_client.requests;
+        assert_eq!(if_none_match(&requests[3]), Some("\"fnd-1\""));
+        assert_eq!(if_none_match(&requests[4]), Some("\"rhrread-1\""));
+        assert_eq!(if_none_match(&requests[5]), Some("\"warnsum-1\""));
+    }
+}
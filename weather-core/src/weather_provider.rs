@@ -0,0 +1,12 @@
+use alloc::vec::Vec;
+
+use crate::model::{ApiError, WeatherForecast, WeatherReport};
+
+/// A weather service the forecast grid can be drawn from
+pub trait WeatherProvider {
+    /// Daily forecasts, starting from today or tomorrow depending on the service
+    fn fetch_forecast(&mut self) -> Result<Vec<WeatherForecast>, ApiError>;
+
+    /// Latest observation for the configured place
+    fn fetch_current_weather(&mut self) -> Result<WeatherReport, ApiError>;
+}